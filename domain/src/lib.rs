@@ -0,0 +1,12 @@
+//! Guild data shared by the worker and sgctl: classes and specs, realms, the member/roster/raid/season
+//! documents kept in R2, and the wowaudit api responses.
+//! Anything that reads or writes those documents should go through these types.
+
+pub mod member;
+pub mod player_metadata;
+pub mod realm;
+pub mod roster_diff;
+pub mod season;
+pub mod time;
+pub mod wow;
+pub mod wowaudit;
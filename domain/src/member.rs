@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::player_metadata::{check_version, parse_document, MetadataError, Player, RosterDocument};
+use crate::realm::{find_realm, profile_path, realm_slug, same_character};
+use crate::time::Region;
+use crate::wow::PlayerClass;
+
+// +---------+
+// | Members |
+// +---------+
+// The people behind the characters: one entry per person with their main, alts, Discord and
+// rank. Lives in R2 as content/members.json (bundled copy in templates/assets/members.json).
+// Sims, /keys, the about page's contacts and roster entries point at a member id, so renaming
+// someone or adding an alt is one change here.
+// content/members.json:
+// { "version": 1, "members": [ { $id, $display_name, $rank, $main, $alts, $discord?, $battlenet? } ] }
+
+/// bump when the layout of members.json changes.
+pub const MEMBERS_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Rank {
+    GuildMaster,
+    Officer,
+    Raider,
+    Trial,
+    Social,
+}
+
+impl Rank {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rank::GuildMaster => "Guild Master",
+            Rank::Officer => "Officer",
+            Rank::Raider => "Raider",
+            Rank::Trial => "Trial",
+            Rank::Social => "Social",
+        }
+    }
+
+    /// listed as a contact on the about page.
+    pub fn is_contact(&self) -> bool {
+        matches!(self, Rank::GuildMaster | Rank::Officer)
+    }
+}
+
+impl std::fmt::Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Character {
+    pub name: String,
+    pub class: PlayerClass,
+    pub realm: String,
+    /// left out for us characters.
+    #[serde(default, skip_serializing_if = "Region::is_us")]
+    pub region: Region,
+    /// listed on /keys (and its leaderboard and score history).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keys: bool,
+}
+
+impl Character {
+    /// see realm::same_character.
+    pub fn is(&self, name: &str, realm: &str, region: Region) -> bool {
+        self.region == region && same_character(&self.name, &self.realm, name, realm)
+    }
+
+    /// their /player page.
+    pub fn profile_path(&self) -> String {
+        profile_path(self.region, &self.realm, &self.name)
+    }
+
+    pub fn realm_label(&self) -> String {
+        self.region.realm_label(&self.realm)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Member {
+    /// lowercase letters, digits and '-' (ex: "nuzz"). what other documents and pages use.
+    pub id: String,
+    /// what the guild calls them (ex: "Nuzz").
+    pub display_name: String,
+    pub rank: Rank,
+    pub main: Character,
+    #[serde(default)]
+    pub alts: Vec<Character>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discord: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battlenet: Option<String>,
+}
+
+impl Member {
+    /// main first, then alts.
+    pub fn characters(&self) -> impl Iterator<Item = &Character> {
+        std::iter::once(&self.main).chain(self.alts.iter())
+    }
+
+    /// one of their characters by name, any realm.
+    pub fn character(&self, name: &str) -> Option<&Character> {
+        let name = name.to_lowercase();
+        self.characters().find(|character| character.name.to_lowercase() == name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemberDocument {
+    pub version: u32,
+    pub members: Vec<Member>,
+}
+
+impl MemberDocument {
+    /// parse and validate a members.json document.
+    pub fn from_json(json: &str) -> Result<Self, MetadataError> {
+        let doc: MemberDocument = parse_document("members.json", json)?;
+        doc.validate()?;
+        Ok(doc)
+    }
+
+    fn validate(&self) -> Result<(), MetadataError> {
+        check_version("members.json", self.version, MEMBERS_SCHEMA_VERSION)?;
+        let mut ids = HashSet::new();
+        let mut characters = HashSet::new();
+        for member in &self.members {
+            let invalid = |reason: String| MetadataError::Invalid { item: member.id.clone(), reason };
+            let id_chars_ok = member
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+            if member.id.is_empty() || !id_chars_ok {
+                return Err(invalid("ids are lowercase letters, digits and '-'".to_string()));
+            }
+            if !ids.insert(member.id.as_str()) {
+                return Err(invalid("id is used by another member".to_string()));
+            }
+            if member.display_name.trim().is_empty() {
+                return Err(invalid("member is missing a display_name".to_string()));
+            }
+            for character in member.characters() {
+                if character.name.trim().is_empty() || character.realm.trim().is_empty() {
+                    return Err(invalid(format!("character {:?} is missing a name or realm", character)));
+                }
+                if let Some(realm) = find_realm(character.region, &character.realm) {
+                    if realm.name != character.realm {
+                        return Err(invalid(format!(
+                            "{}-{}: write the realm as \"{}\"",
+                            character.name, character.realm, realm.name
+                        )));
+                    }
+                }
+                let key = (character.name.to_lowercase(), realm_slug(&character.realm), character.region);
+                if !characters.insert(key) {
+                    return Err(invalid(format!(
+                        "{}-{} belongs to more than one member",
+                        character.name, character.realm
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn member(&self, id: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.id == id)
+    }
+
+    /// whose character this is.
+    pub fn find_character(&self, name: &str, realm: &str, region: Region) -> Option<&Member> {
+        self.members
+            .iter()
+            .find(|member| member.characters().any(|character| character.is(name, realm, region)))
+    }
+
+    /// the member behind a roster entry: its `member` id, or else whoever owns the character.
+    pub fn for_player(&self, player: &Player) -> Option<&Member> {
+        match &player.member {
+            Some(id) => self.member(id),
+            None => self.find_character(&player.name, &player.realm, player.region),
+        }
+    }
+
+    /// every member id a roster references must exist.
+    pub fn check_roster(&self, rosters: &RosterDocument) -> Result<(), MetadataError> {
+        for (roster, players) in &rosters.rosters {
+            for player in players {
+                if let Some(id) = &player.member {
+                    if self.member(id).is_none() {
+                        return Err(MetadataError::Invalid {
+                            item: roster.clone(),
+                            reason: format!("{}-{} references unknown member \"{}\"", player.name, player.realm, id),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// every character listed on /keys, with its member.
+    pub fn keys_characters(&self) -> impl Iterator<Item = (&Member, &Character)> {
+        self.members
+            .iter()
+            .flat_map(|member| member.characters().map(move |character| (member, character)))
+            .filter(|(_, character)| character.keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(members: &str) -> Result<MemberDocument, MetadataError> {
+        MemberDocument::from_json(&format!(r#"{{ "version": 1, "members": [{}] }}"#, members))
+    }
+
+    fn member(id: &str, name: &str, realm: &str) -> String {
+        format!(
+            r#"{{ "id": "{}", "display_name": "{}", "rank": "Raider", "main": {{ "name": "{}", "class": "Mage", "realm": "{}" }} }}"#,
+            id, id, name, realm
+        )
+    }
+
+    fn rejected(result: Result<MemberDocument, MetadataError>) -> (String, String) {
+        match result {
+            Err(MetadataError::Invalid { item, reason }) => (item, reason),
+            result => panic!("expected members.json to be rejected, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn bundled_members_are_valid() {
+        let members = MemberDocument::from_json(include_str!("../../templates/assets/members.json")).unwrap();
+        assert!(members.keys_characters().count() > 0);
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let (item, reason) = rejected(document(&[member("pip", "Piptide", "Tichondrius"), member("pip", "Pipmeow", "Tichondrius")].join(",")));
+
+        assert_eq!(item, "pip");
+        assert_eq!(reason, "id is used by another member");
+    }
+
+    #[test]
+    fn id_charset() {
+        for id in ["Pip", "pip tide", "pip_tide", "pïp", ""] {
+            let (_, reason) = rejected(document(&member(id, "Piptide", "Tichondrius")));
+            assert_eq!(reason, "ids are lowercase letters, digits and '-'", "{:?}", id);
+        }
+        assert!(document(&member("pip-2", "Piptide", "Tichondrius")).is_ok());
+    }
+
+    #[test]
+    fn a_character_has_one_member() {
+        let (item, reason) = rejected(document(&[member("pip", "Piptide", "Area 52"), member("nuzz", "piptide", "Area 52")].join(",")));
+        assert_eq!(item, "nuzz");
+        assert_eq!(reason, "piptide-Area 52 belongs to more than one member");
+
+        // realms outside the catalogue are compared by slug.
+        let (_, reason) = rejected(document(&[member("pip", "Piptide", "Old Realm"), member("nuzz", "Piptide", "old-realm")].join(",")));
+        assert_eq!(reason, "Piptide-old-realm belongs to more than one member");
+
+        // the same name on another realm is another character.
+        assert!(document(&[member("pip", "Piptide", "Area 52"), member("nuzz", "Piptide", "Stormrage")].join(",")).is_ok());
+    }
+
+    #[test]
+    fn catalogue_realms_use_the_catalogue_spelling() {
+        // "Area-52" is still Area 52, so it can't sneak past the duplicate check either.
+        let (item, reason) = rejected(document(&[member("pip", "Piptide", "Area 52"), member("nuzz", "Piptide", "Area-52")].join(",")));
+        assert_eq!(item, "nuzz");
+        assert_eq!(reason, "Piptide-Area-52: write the realm as \"Area 52\"");
+
+        let (_, reason) = rejected(document(&member("pip", "Piptide", "mal'ganis")));
+        assert_eq!(reason, "Piptide-mal'ganis: write the realm as \"Mal'Ganis\"");
+    }
+
+    fn rosters(players: &str) -> RosterDocument {
+        RosterDocument::from_json(&format!(r#"{{ "version": 1, "rosters": {{ "Dimensius": [{}] }} }}"#, players)).unwrap()
+    }
+
+    #[test]
+    fn roster_member_ids_must_exist() {
+        let members = document(&member("pip", "Piptide", "Tichondrius")).unwrap();
+        let known = rosters(r#"{ "name": "Pipmeow", "class": "Druid", "realm": "Tichondrius", "role": "Healer", "member": "pip" }"#);
+        assert!(members.check_roster(&known).is_ok());
+
+        let unknown = rosters(r#"{ "name": "Nuzz", "class": "Druid", "realm": "Stormrage", "role": "Tank", "member": "nuzz" }"#);
+        match members.check_roster(&unknown) {
+            Err(MetadataError::Invalid { item, reason }) => {
+                assert_eq!(item, "Dimensius");
+                assert_eq!(reason, "Nuzz-Stormrage references unknown member \"nuzz\"");
+            }
+            result => panic!("expected the roster to be rejected, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn roster_entries_find_their_member() {
+        let members = document(&[member("pip", "Piptide", "Tichondrius"), member("nuzz", "Nuzz", "Stormrage")].join(",")).unwrap();
+        let owner = |player: &str| {
+            let doc = rosters(player);
+            members.for_player(&doc.rosters["Dimensius"][0]).map(|member| member.id.clone())
+        };
+
+        assert_eq!(owner(r#"{ "name": "Piptide", "class": "Mage", "realm": "Tichondrius", "role": "Dps" }"#).as_deref(), Some("pip"));
+        // an explicit member id wins over whoever owns the character.
+        assert_eq!(
+            owner(r#"{ "name": "Piptide", "class": "Mage", "realm": "Tichondrius", "role": "Dps", "member": "nuzz" }"#).as_deref(),
+            Some("nuzz")
+        );
+        assert_eq!(owner(r#"{ "name": "Piptide", "class": "Mage", "realm": "Tichondrius", "role": "Dps", "region": "eu" }"#), None);
+        assert_eq!(owner(r#"{ "name": "Nobody", "class": "Mage", "realm": "Stormrage", "role": "Dps" }"#), None);
+    }
+}
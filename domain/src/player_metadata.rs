@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, HashSet};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::realm::{armory_url, find_realm, profile_path, same_character};
+use crate::season::SeasonConfig;
+use crate::time::{Region, GUILD_ZONE};
+use crate::wow::{class_label, ArmorType, PlayerClass, PlayerRole, Spec};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Player {
+    pub name: String,
+    pub class: PlayerClass,
+    pub realm: String,
+    /// left out for us characters.
+    #[serde(default, skip_serializing_if = "Region::is_us")]
+    pub region: Region,
+    /// id in members.json, for characters no member lists (ex: before a realm transfer).
+    /// others are matched to their member by name and realm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    pub role: PlayerRole,
+    /// main spec, when we know it. must belong to `class`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<Spec>,
+}
+
+impl Player {
+    /// "Devastation Evoker", or just "Evoker" when the spec isn't set.
+    pub fn class_label(&self) -> String {
+        class_label(self.class, self.spec)
+    }
+
+    pub fn armor_type(&self) -> ArmorType {
+        match self.spec {
+            Some(spec) => spec.armor_type(),
+            None => self.class.armor_type(),
+        }
+    }
+
+    pub fn armory_url(&self) -> String {
+        armory_url(self.region, &self.realm, &self.name)
+    }
+
+    /// their /player page.
+    pub fn profile_path(&self) -> String {
+        profile_path(self.region, &self.realm, &self.name)
+    }
+
+    /// see realm::same_character.
+    pub fn is(&self, name: &str, realm: &str, region: Region) -> bool {
+        self.region == region && same_character(&self.name, &self.realm, name, realm)
+    }
+
+    pub fn realm_label(&self) -> String {
+        self.region.realm_label(&self.realm)
+    }
+
+    /// listed in a different role than their spec normally plays (ex: a Holy Paladin as Dps).
+    pub fn is_off_spec(&self) -> bool {
+        self.spec.is_some_and(|spec| spec.default_role() != self.role)
+    }
+}
+
+
+// +------------------+
+// | Documents in R2  |
+// +------------------+
+// Rosters, raid kills and seasons (see season.rs) live in R2 as versioned json documents.
+// A copy of each is bundled under templates/assets/ as a fallback when R2 is unreachable.
+// The worker loads them (src/player_metadata.rs), sgctl writes them, both parse them here.
+
+/// bump when the layout of rosters.json changes.
+pub const ROSTERS_SCHEMA_VERSION: u32 = 1;
+/// bump when the layout of raids.json changes.
+/// v2: kills reference a season id from seasons.json instead of season/expansion names.
+/// v3: datetime is the full kill time (RFC 3339), pretty_datetime is derived from it.
+pub const RAIDS_SCHEMA_VERSION: u32 = 3;
+
+#[derive(Debug)]
+pub enum MetadataError {
+    Fetch(String),
+    Parse { document: &'static str, reason: String },
+    UnsupportedVersion { document: &'static str, version: u32, expected: u32 },
+    Invalid { item: String, reason: String },
+    UnknownRoster(String),
+}
+
+impl std::fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataError::Fetch(e) => write!(f, "Unable to load guild data: {}", e),
+            MetadataError::Parse { document, reason } => {
+                write!(f, "{} is not valid: {}", document, reason)
+            }
+            MetadataError::UnsupportedVersion { document, version, expected } => write!(
+                f,
+                "{} version {} is not supported (expected {})",
+                document, version, expected
+            ),
+            MetadataError::Invalid { item, reason } => {
+                write!(f, "'{}' is not valid: {}", item, reason)
+            }
+            MetadataError::UnknownRoster(id) => write!(f, "No roster found for '{}'", id),
+        }
+    }
+}
+
+pub(crate) fn parse_document<T: serde::de::DeserializeOwned>(
+    document: &'static str,
+    json: &str,
+) -> Result<T, MetadataError> {
+    serde_json::from_str(json).map_err(|e| MetadataError::Parse {
+        document,
+        reason: e.to_string(),
+    })
+}
+
+pub(crate) fn check_version(
+    document: &'static str,
+    version: u32,
+    expected: u32,
+) -> Result<(), MetadataError> {
+    if version != expected {
+        return Err(MetadataError::UnsupportedVersion { document, version, expected });
+    }
+    Ok(())
+}
+
+// +------------+
+// | Raid Kills |
+// +------------+
+// content/raids.json:
+// { "version": 3, "kills": [ { $fight_name, $season, $group_photo, $log_id, ... } ] }
+
+/// include a video:
+/// - set fight_is_video: true
+/// - ensure video in r2 follows pattern: $group_photo-video.mp4 (poster is $group_photo.png)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RaidMetaData {
+    pub fight_name: String,
+    /// id of the season in seasons.json (ex: "tww-s3").
+    pub season: String,
+    pub group_photo: String,
+    pub log_id: String,
+    /// when the boss died, RFC 3339 (ex: "2025-12-19T03:45:00Z").
+    pub datetime: DateTime<Utc>,
+    /// id of the roster in rosters.json that got the kill.
+    pub fight_key: String,
+    #[serde(default)]
+    pub fight_is_video: bool,
+}
+
+impl RaidMetaData {
+    /// "10:45pm - 18 December 2025", in the guild's timezone.
+    pub fn pretty_datetime(&self) -> String {
+        GUILD_ZONE.format(self.datetime, "%-I:%M%P - %-d %B %Y")
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RaidManifest {
+    pub version: u32,
+    pub kills: Vec<RaidMetaData>,
+}
+
+impl RaidManifest {
+    /// parse and validate a raids.json document. kills are sorted newest first.
+    pub fn from_json(json: &str, seasons: &SeasonConfig) -> Result<Self, MetadataError> {
+        let mut manifest: RaidManifest = parse_document("raids.json", json)?;
+        manifest.validate(seasons)?;
+        manifest.kills.sort_by_key(|kill| std::cmp::Reverse(kill.datetime));
+        Ok(manifest)
+    }
+
+    fn validate(&self, seasons: &SeasonConfig) -> Result<(), MetadataError> {
+        check_version("raids.json", self.version, RAIDS_SCHEMA_VERSION)?;
+        let mut seen = HashSet::new();
+        for kill in &self.kills {
+            let invalid = |reason: &str| MetadataError::Invalid {
+                item: kill.fight_name.clone(),
+                reason: reason.to_string(),
+            };
+            if kill.fight_name.trim().is_empty() || kill.fight_key.trim().is_empty() {
+                return Err(invalid("kill is missing a fight_name or fight_key"));
+            }
+            if seasons.season(&kill.season).is_none() {
+                return Err(invalid("season is not listed in seasons.json"));
+            }
+            if !seen.insert(kill.fight_key.as_str()) {
+                return Err(invalid("fight_key is used by another kill"));
+            }
+        }
+        Ok(())
+    }
+
+    /// every kill's fight_key must name a roster in rosters.json.
+    pub fn check_rosters(&self, rosters: &RosterDocument) -> Result<(), MetadataError> {
+        match self.kills.iter().find(|kill| !rosters.rosters.contains_key(&kill.fight_key)) {
+            Some(kill) => Err(MetadataError::Invalid {
+                item: kill.fight_name.clone(),
+                reason: format!("fight_key \"{}\" has no roster in rosters.json", kill.fight_key),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+// +---------+
+// | Rosters |
+// +---------+
+// content/rosters.json:
+// { "version": 1, "rosters": { $SEASON: [ { $name, $class, $realm, $role, $region?, $member? } ] } }
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RosterDocument {
+    pub version: u32,
+    /// sorted by id so written documents diff cleanly.
+    pub rosters: BTreeMap<String, Vec<Player>>,
+}
+
+impl RosterDocument {
+    /// parse and validate a rosters.json document.
+    pub fn from_json(json: &str) -> Result<Self, MetadataError> {
+        let doc: RosterDocument = parse_document("rosters.json", json)?;
+        doc.validate()?;
+        Ok(doc)
+    }
+
+    // serde checks the shape, this checks the contents.
+    fn validate(&self) -> Result<(), MetadataError> {
+        check_version("rosters.json", self.version, ROSTERS_SCHEMA_VERSION)?;
+        for (id, players) in &self.rosters {
+            let invalid = |reason: String| MetadataError::Invalid { item: id.clone(), reason };
+            if players.is_empty() {
+                return Err(invalid("roster has no players".to_string()));
+            }
+            let mut seen = HashSet::new();
+            for player in players {
+                if player.name.trim().is_empty() || player.realm.trim().is_empty() {
+                    return Err(invalid(format!("player {:?} is missing a name or realm", player)));
+                }
+                if let Some(spec) = player.spec {
+                    if spec.class() != player.class {
+                        return Err(invalid(format!(
+                            "{}-{} is a {} but has spec {:?}",
+                            player.name, player.realm, player.class, spec
+                        )));
+                    }
+                }
+                if let Some(realm) = find_realm(player.region, &player.realm) {
+                    if realm.name != player.realm {
+                        return Err(invalid(format!(
+                            "{}-{}: write the realm as \"{}\"",
+                            player.name, player.realm, realm.name
+                        )));
+                    }
+                }
+                if !seen.insert((player.name.as_str(), player.realm.as_str(), player.region)) {
+                    return Err(invalid(format!("{}-{} is listed twice", player.name, player.realm)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Get the players for a roster id (the raid's fight_key, ex: "Gallywix").
+pub fn build_roster(doc: &RosterDocument, id: &str) -> Result<Vec<Player>, MetadataError> {
+    doc.rosters
+        .get(id)
+        .cloned()
+        .ok_or_else(|| MetadataError::UnknownRoster(id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the bundled copies the worker falls back to.
+    fn documents() -> (RaidManifest, RosterDocument) {
+        let seasons = SeasonConfig::from_json(include_str!("../../templates/assets/seasons.json")).unwrap();
+        let raids = RaidManifest::from_json(include_str!("../../templates/assets/raids.json"), &seasons).unwrap();
+        let rosters = RosterDocument::from_json(include_str!("../../templates/assets/rosters.json")).unwrap();
+        (raids, rosters)
+    }
+
+    #[test]
+    fn every_bundled_kill_has_a_roster() {
+        let (raids, rosters) = documents();
+
+        raids.check_rosters(&rosters).unwrap();
+    }
+
+    #[test]
+    fn kill_without_a_roster_is_rejected() {
+        let (mut raids, rosters) = documents();
+        raids.kills[0].fight_key = "Ragnaros".to_string();
+
+        match raids.check_rosters(&rosters) {
+            Err(MetadataError::Invalid { item, reason }) => {
+                assert_eq!(item, raids.kills[0].fight_name);
+                assert_eq!(reason, "fight_key \"Ragnaros\" has no roster in rosters.json");
+            }
+            result => panic!("expected the kill to be rejected, got {:?}", result),
+        }
+    }
+}
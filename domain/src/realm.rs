@@ -0,0 +1,523 @@
+use crate::time::Region;
+
+// +-----------------+
+// | Realm Catalogue |
+// +-----------------+
+// Every US and EU realm, grouped the way Blizzard connects them. Display names and slugs come
+// from here, so "Area 52", "Area-52" and "area52" all end up as "Area 52" / "area-52", and every
+// armory, raider.io and warcraftlogs link is built from the catalogue's slug.
+// Russian realms are left out, their Cyrillic names don't slug the same way: they get no
+// catalogue spelling, no autocomplete and are slugged as written.
+// Source: Blizzard's connected-realm index, update it when realms merge.
+
+/// Connected realm groups in the US (and Oceanic/Latin/Brazil) region.
+const US_REALMS: &[&[&str]] = &[
+    &["Aegwynn", "Bonechewer", "Daggerspine", "Gurubashi", "Hakkar"],
+    &["Aerie Peak"],
+    &["Agamaggan", "Archimonde", "Burning Legion", "Jaedenar", "The Underbog"],
+    &["Aggramar", "Fizzcrank"],
+    &["Akama", "Dragonmaw", "Mug'thol"],
+    &["Alexstrasza", "Terokkar"],
+    &["Alleria", "Khadgar"],
+    &["Altar of Storms", "Anetheron", "Magtheridon", "Ysondre"],
+    &["Alterac Mountains", "Balnazzar", "Gorgonnash", "The Forgotten Coast", "Undermine"],
+    &["Aman'Thul"],
+    &["Andorhal", "Scilla", "Ursin", "Zuluhed"],
+    &["Antonidas", "Uldum"],
+    &["Anub'arak", "Chromaggus", "Crushridge", "Garithos", "Nathrezim", "Smolderthorn"],
+    &["Anvilmar"],
+    &["Arathor", "Drenden"],
+    &["Area 52"],
+    &["Argent Dawn", "The Scryers"],
+    &["Arygos", "Llane"],
+    &["Auchindoun", "Cho'gall", "Laughing Skull"],
+    &["Azgalor", "Azshara", "Destromath", "Thunderlord"],
+    &["Azjol-Nerub", "Khaz Modan"],
+    &["Azralon", "Gallywix", "Goldrinn", "Nemesis", "Tol Barad"],
+    &["Azuremyst", "Staghelm"],
+    &["Baelgun", "Doomhammer"],
+    &["Barthilas"],
+    &["Black Dragonflight", "Gul'dan", "Skullcrusher"],
+    &["Blackhand", "Galakrond"],
+    &["Blackrock"],
+    &["Blackwater Raiders", "Shadow Council"],
+    &["Blackwing Lair", "Dethecus", "Detheroc", "Haomarush", "Lethon", "Shadowmoon"],
+    &["Blade's Edge", "Thunderhorn"],
+    &["Bladefist", "Kul Tiras"],
+    &["Bleeding Hollow"],
+    &["Blood Furnace", "Mannoroth", "Nazjatar"],
+    &["Bloodhoof", "Duskwood"],
+    &["Bloodscalp", "Boulderfist", "Dunemaul", "Maiev", "Stonemaul"],
+    &["Borean Tundra", "Shadowsong"],
+    &["Bronzebeard", "Shandris"],
+    &["Burning Blade", "Lightning's Blade", "Onyxia"],
+    &["Caelestrasz", "Nagrand", "Saurfang"],
+    &["Cairne", "Perenolde"],
+    &["Cenarion Circle", "Sisters of Elune"],
+    &["Cenarius"],
+    &["Coilfang", "Dalvengyr", "Dark Iron", "Demon Soul"],
+    &["Dalaran"],
+    &["Darkspear"],
+    &["Darrowmere", "Windrunner"],
+    &["Dath'Remar", "Khaz'goroth"],
+    &["Dawnbringer", "Madoran"],
+    &["Deathwing", "Executus", "Kalecgos", "Shattered Halls"],
+    &["Dentarg", "Whisperwind"],
+    &["Draenor", "Echo Isles"],
+    &["Dragonblight", "Fenris"],
+    &["Draka", "Suramar"],
+    &["Drak'Tharon", "Firetree", "Malorne", "Rivendare", "Spirestone", "Stormscale"],
+    &["Drak'thul", "Skywall"],
+    &["Drakkari"],
+    &["Dreadmaul", "Gundrak", "Jubei'Thos", "Thaurissan"],
+    &["Durotan", "Ysera"],
+    &["Earthen Ring"],
+    &["Eitrigg", "Shu'halo"],
+    &["Eldre'Thalas", "Korialstrasz"],
+    &["Elune", "Gilneas"],
+    &["Emerald Dream"],
+    &["Eonar", "Velen"],
+    &["Eredar", "Gorefiend", "Spinebreaker", "Wildhammer"],
+    &["Exodar", "Medivh"],
+    &["Farstriders", "Silver Hand", "Thorium Brotherhood"],
+    &["Feathermoon", "Scarlet Crusade"],
+    &["Frostmane", "Ner'zhul", "Tortheldrin"],
+    &["Frostmourne"],
+    &["Frostwolf", "Vashj"],
+    &["Garona"],
+    &["Garrosh"],
+    &["Ghostlands", "Kael'thas"],
+    &["Gnomeregan", "Moonrunner"],
+    &["Greymane", "Tanaris"],
+    &["Grizzly Hills", "Lothar"],
+    &["Hellscream", "Zangarmarsh"],
+    &["Hydraxis", "Terenas"],
+    &["Hyjal"],
+    &["Icecrown", "Malygos"],
+    &["Illidan"],
+    &["Kargath", "Norgannon"],
+    &["Kel'Thuzad"],
+    &["Kil'jaeden"],
+    &["Kilrogg", "Winterhoof"],
+    &["Kirin Tor", "Sentinels", "Steamwheedle Cartel"],
+    &["Korgath"],
+    &["Lightbringer"],
+    &["Lightninghoof", "Maelstrom", "The Venture Co"],
+    &["Mal'Ganis"],
+    &["Malfurion", "Trollbane"],
+    &["Misha", "Rexxar"],
+    &["Mok'Nathal", "Silvermoon"],
+    &["Moon Guard"],
+    &["Muradin", "Nordrassil"],
+    &["Nazgrel", "Nesingwary", "Vek'nilash"],
+    &["Proudmoore"],
+    &["Quel'dorei", "Sen'jin"],
+    &["Quel'Thalas"],
+    &["Ragnaros"],
+    &["Ravencrest", "Uldaman"],
+    &["Ravenholdt", "Twisting Nether"],
+    &["Remulos"],
+    &["Runetotem", "Uther"],
+    &["Sargeras"],
+    &["Shattered Hand"],
+    &["Stormrage"],
+    &["Thrall"],
+    &["Tichondrius"],
+    &["Turalyon"],
+    &["Wyrmrest Accord"],
+    &["Zul'jin"],
+];
+
+/// Connected realm groups in the EU region (English, German, French, Spanish, Italian and
+/// Portuguese realms).
+const EU_REALMS: &[&[&str]] = &[
+    // English
+    &["Aerie Peak", "Bronzebeard"],
+    &["Aggra (Português)", "Grim Batol"],
+    &["Aggramar", "Hellscream"],
+    &["Agamaggan", "Bloodscalp", "Crushridge", "Emeriss", "Hakkar", "Twilight's Hammer"],
+    &["Ahn'Qiraj", "Balnazzar", "Boulderfist", "Chromaggus", "Daggerspine", "Laughing Skull", "Shattered Halls", "Sunstrider", "Talnivarr", "Trollbane"],
+    &["Al'Akir", "Skullcrusher", "Xavius"],
+    &["Alonsus", "Anachronos", "Kul Tiras"],
+    &["Arathor", "Hellfire"],
+    &["Argent Dawn"],
+    &["Aszune", "Shadowsong"],
+    &["Auchindoun", "Dunemaul", "Jaedenar"],
+    &["Azjol-Nerub", "Quel'Thalas"],
+    &["Azuremyst", "Stormrage"],
+    &["Bladefist", "Frostwhisper", "Zenedar"],
+    &["Bloodfeather", "Burning Steppes", "Executus", "Kor'gall", "Shattered Hand"],
+    &["Bloodhoof", "Khadgar"],
+    &["Bronze Dragonflight", "Nordrassil"],
+    &["Burning Blade", "Drak'thul"],
+    &["Burning Legion"],
+    &["Chamber of Aspects"],
+    &["Darkmoon Faire", "Earthen Ring"],
+    &["Darksorrow", "Genjuros", "Neptulon"],
+    &["Darkspear", "Saurfang", "Terokkar"],
+    &["Deathwing", "Dragonblight", "Ghostlands", "Karazhan", "Lightning's Blade", "The Maelstrom"],
+    &["Defias Brotherhood", "Ravenholdt", "Scarshield Legion", "Sporeggar", "The Venture Co"],
+    &["Dentarg", "Tarren Mill"],
+    &["Doomhammer", "Turalyon"],
+    &["Draenor"],
+    &["Dragonmaw", "Haomarush", "Spinebreaker", "Stormreaver", "Vashj"],
+    &["Emerald Dream", "Terenas"],
+    &["Eonar"],
+    &["Frostmane"],
+    &["Kazzak"],
+    &["Kilrogg", "Nagrand", "Runetotem"],
+    &["Lightbringer", "Mazrigos"],
+    &["Magtheridon"],
+    &["Moonglade", "Steamwheedle Cartel", "The Sha'tar"],
+    &["Outland"],
+    &["Ragnaros"],
+    &["Ravencrest"],
+    &["Silvermoon"],
+    &["Stormscale"],
+    &["Sylvanas"],
+    &["Thunderhorn", "Wildhammer"],
+    &["Twisting Nether"],
+    // German
+    &["Aegwynn"],
+    &["Alexstrasza", "Nethersturm"],
+    &["Alleria", "Rexxar"],
+    &["Ambossar", "Kargath"],
+    &["Anetheron", "Festung der Stürme", "Gul'dan", "Kil'jaeden", "Nathrezim", "Rajaxx"],
+    &["Antonidas"],
+    &["Anub'arak", "Dalvengyr", "Frostmourne", "Nazjatar", "Zuluhed"],
+    &["Area 52", "Sen'jin", "Un'Goro"],
+    &["Arthas", "Blutkessel", "Kel'Thuzad", "Vek'lor", "Wrathbringer"],
+    &["Arygos", "Khaz'goroth"],
+    &["Azshara", "Krag'jin"],
+    &["Baelgun", "Lothar"],
+    &["Blackhand", "Mal'Ganis"],
+    &["Blackmoore"],
+    &["Blackrock"],
+    &["Das Konsortium", "Das Syndikat", "Der abyssische Rat", "Die Arguswacht", "Die Todeskrallen", "Kult der Verdammten"],
+    &["Der Mithrilorden", "Der Rat von Dalaran"],
+    &["Destromath", "Gorgonnash", "Mannoroth", "Nefarian", "Nera'thor"],
+    &["Dethecus", "Mug'thol", "Onyxia", "Terrordar", "Theradras"],
+    &["Die Aldor"],
+    &["Die ewige Wacht", "Die Silberne Hand"],
+    &["Die Nachtwache", "Forscherliga"],
+    &["Dun Morogh", "Norgannon"],
+    &["Durotan", "Tirion"],
+    &["Echsenkessel", "Taerar"],
+    &["Eredar"],
+    &["Frostwolf"],
+    &["Garrosh", "Nozdormu", "Shattrath"],
+    &["Gilneas", "Ulduar"],
+    &["Lordaeron", "Tichondrius"],
+    &["Madmortem", "Proudmoore"],
+    &["Malfurion", "Malygos"],
+    &["Malorne", "Ysera"],
+    &["Perenolde", "Teldrassil"],
+    &["Thrall"],
+    &["Todeswache", "Zirkel des Cenarius"],
+    // French
+    &["Arak-arahm", "Kael'thas", "Rashgarroth", "Throk'Feroth"],
+    &["Arathi", "Naxxramas", "Temple noir", "Ysondre"],
+    &["Archimonde"],
+    &["Chants éternels", "Vol'jin"],
+    &["Cho'gall", "Dalaran", "Eldre'Thalas", "Marécage de Zangar", "Sinstralis"],
+    &["Confrérie du Thorium", "Les Clairvoyants", "Les Sentinelles"],
+    &["Conseil des Ombres", "Culte de la Rive noire", "La Croisade écarlate"],
+    &["Drek'Thar", "Eitrigg", "Krasus", "Uldaman"],
+    &["Elune", "Varimathras"],
+    &["Garona", "Ner'zhul", "Sargeras"],
+    &["Hyjal"],
+    &["Illidan"],
+    &["Kirin Tor"],
+    &["Medivh", "Suramar"],
+    // Spanish
+    &["C'Thun", "Dun Modr"],
+    &["Colinas Pardas", "Los Errantes", "Tyrande"],
+    &["Exodar", "Minahonda"],
+    &["Sanguino", "Shen'dralar", "Uldum", "Zul'jin"],
+    // Italian
+    &["Nemesis"],
+    &["Pozzo dell'Eternità"],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Realm {
+    pub name: &'static str,
+    pub region: Region,
+    group: &'static [&'static str],
+}
+
+impl Realm {
+    /// blizzard's slug: "Mal'Ganis" -> "malganis", "Area 52" -> "area-52", "Chants éternels" -> "chants-eternels".
+    pub fn slug(&self) -> String {
+        slugify(self.name)
+    }
+
+    /// the other realms it is connected to, empty for a standalone realm.
+    pub fn connected(&self) -> Vec<Realm> {
+        self.group
+            .iter()
+            .filter(|name| **name != self.name)
+            .map(|name| Realm { name, region: self.region, group: self.group })
+            .collect()
+    }
+}
+
+/// every realm in `region`, in catalogue (connected group) order.
+pub fn realms(region: Region) -> impl Iterator<Item = Realm> {
+    let groups = match region {
+        Region::Us => US_REALMS,
+        Region::Eu => EU_REALMS,
+    };
+    groups
+        .iter()
+        .flat_map(move |group| group.iter().map(move |name| Realm { name, region, group }))
+}
+
+/// The catalogue realm `input` refers to, however it was written: "Area 52", "area-52", "AREA52".
+pub fn find_realm(region: Region, input: &str) -> Option<Realm> {
+    let key = match_key(input);
+    if key.is_empty() {
+        return None;
+    }
+    realms(region).find(|realm| match_key(realm.name) == key)
+}
+
+/// Realms whose name starts with `query` first, then the ones containing it, alphabetical
+/// within each. Both regions when `region` is None, at most `limit`.
+pub fn search_realms(region: Option<Region>, query: &str, limit: usize) -> Vec<Realm> {
+    let key = match_key(query);
+    let regions = match region {
+        Some(region) => vec![region],
+        None => vec![Region::Us, Region::Eu],
+    };
+    let mut matches: Vec<(bool, Realm)> = regions
+        .into_iter()
+        .flat_map(realms)
+        .filter_map(|realm| {
+            let name = match_key(realm.name);
+            match (name.starts_with(&key), name.contains(&key)) {
+                (true, _) => Some((false, realm)),
+                (false, true) => Some((true, realm)),
+                _ => None,
+            }
+        })
+        .collect();
+    matches.sort_by(|(a_later, a), (b_later, b)| {
+        a_later
+            .cmp(b_later)
+            .then_with(|| match_key(a.name).cmp(&match_key(b.name)))
+    });
+    matches.into_iter().map(|(_, realm)| realm).take(limit).collect()
+}
+
+/// The slug for any realm string. Catalogue realms (either region) use the catalogue's name,
+/// anything else is slugged as written, the same way. Hyphens are kept as word breaks there so a
+/// slug slugs to itself.
+pub fn realm_slug(realm: &str) -> String {
+    match find_realm(Region::Us, realm).or_else(|| find_realm(Region::Eu, realm)) {
+        Some(known) => known.slug(),
+        None => slugify(&realm.replace('-', " ")),
+    }
+}
+
+/// Whether two name-realm pairs are the same character: names compare case-insensitively,
+/// realms by slug ("Area 52" and "area-52").
+pub fn same_character(name: &str, realm: &str, other_name: &str, other_realm: &str) -> bool {
+    name.to_lowercase() == other_name.to_lowercase() && realm_slug(realm) == realm_slug(other_realm)
+}
+
+/// Display form of a realm name, the catalogue's spelling when it is a known realm.
+/// Otherwise trimmed with single spaces, and slugs ("wyrmrest-accord") title cased back.
+pub fn normalize_realm(realm: &str) -> String {
+    if let Some(known) = find_realm(Region::Us, realm).or_else(|| find_realm(Region::Eu, realm)) {
+        return known.name.to_string();
+    }
+    let realm = realm.split_whitespace().collect::<Vec<_>>().join(" ");
+    if realm.chars().any(char::is_uppercase) {
+        return realm;
+    }
+    realm
+        .split(['-', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// lowercase, accents folded, apostrophes/hyphens/brackets dropped, words joined with '-'.
+fn slugify(name: &str) -> String {
+    fold_accents(&name.to_lowercase())
+        .chars()
+        .filter(|c| !matches!(c, '\'' | '’' | '-' | '(' | ')'))
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// what two spellings of a realm have in common: lowercase letters and digits only.
+fn match_key(name: &str) -> String {
+    fold_accents(&name.to_lowercase())
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect()
+}
+
+fn fold_accents(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ä' | 'ã' => 'a',
+            'ç' => 'c',
+            'è' | 'é' | 'ê' | 'ë' => 'e',
+            'ì' | 'í' | 'î' | 'ï' => 'i',
+            'ñ' => 'n',
+            'ò' | 'ó' | 'ô' | 'ö' | 'õ' => 'o',
+            'ù' | 'ú' | 'û' | 'ü' => 'u',
+            other => other,
+        })
+        .collect()
+}
+
+// +-------+
+// | Links |
+// +-------+
+
+/// this site's profile page: "/player/us/area-52/aphitari"
+pub fn profile_path(region: Region, realm: &str, name: &str) -> String {
+    format!("/player/{}/{}/{}", region, realm_slug(realm), name.trim().to_lowercase())
+}
+
+/// "https://worldofwarcraft.blizzard.com/en-us/character/us/area-52/aphitari"
+pub fn armory_url(region: Region, realm: &str, name: &str) -> String {
+    let locale = match region {
+        Region::Us => "en-us",
+        Region::Eu => "en-gb",
+    };
+    format!(
+        "https://worldofwarcraft.blizzard.com/{}/character/{}/{}/{}",
+        locale,
+        region,
+        realm_slug(realm),
+        name.trim().to_lowercase()
+    )
+}
+
+/// "https://raider.io/characters/us/area-52/Aphitari"
+pub fn raiderio_url(region: Region, realm: &str, name: &str) -> String {
+    format!("https://raider.io/characters/{}/{}/{}", region, realm_slug(realm), name.trim())
+}
+
+/// "https://www.warcraftlogs.com/character/us/area-52/aphitari"
+pub fn warcraftlogs_url(region: Region, realm: &str, name: &str) -> String {
+    format!(
+        "https://www.warcraftlogs.com/character/{}/{}/{}",
+        region,
+        realm_slug(realm),
+        name.trim().to_lowercase()
+    )
+}
+
+/// "https://raider.io/guilds/us/stormrage/Seems%20Good"
+pub fn raiderio_guild_url(region: Region, realm: &str, guild: &str) -> String {
+    format!(
+        "https://raider.io/guilds/{}/{}/{}",
+        region,
+        realm_slug(realm),
+        guild.trim().replace(' ', "%20")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(realms: &[Realm]) -> Vec<String> {
+        realms.iter().map(|realm| format!("{}-{}", realm.name, realm.region)).collect()
+    }
+
+    #[test]
+    fn any_spelling_finds_the_realm() {
+        for input in ["Area 52", "Area-52", "area52", " AREA 52 "] {
+            assert_eq!(find_realm(Region::Us, input).map(|realm| realm.name), Some("Area 52"), "{}", input);
+        }
+        assert_eq!(find_realm(Region::Us, "Mal'ganis").map(|realm| realm.name), Some("Mal'Ganis"));
+        assert_eq!(find_realm(Region::Us, "malganis").map(|realm| realm.slug()).as_deref(), Some("malganis"));
+        assert_eq!(find_realm(Region::Us, "Ravencrest Nowhere"), None);
+        assert_eq!(find_realm(Region::Us, "-"), None);
+    }
+
+    #[test]
+    fn connected_realms_leave_out_the_realm_itself() {
+        let realm = find_realm(Region::Eu, "Mal'Ganis").unwrap();
+        let connected: Vec<&str> = realm.connected().iter().map(|realm| realm.name).collect();
+
+        assert_eq!(connected, ["Blackhand"]);
+        assert!(find_realm(Region::Us, "Mal'Ganis").unwrap().connected().is_empty());
+    }
+
+    #[test]
+    fn prefix_matches_come_first() {
+        assert_eq!(
+            names(&search_realms(Some(Region::Us), "moon", 10)),
+            ["Moon Guard-us", "Moonrunner-us", "Feathermoon-us", "Shadowmoon-us", "Silvermoon-us"]
+        );
+        assert_eq!(
+            names(&search_realms(Some(Region::Us), "storm", 10)),
+            ["Stormrage-us", "Stormscale-us", "Altar of Storms-us"]
+        );
+    }
+
+    #[test]
+    fn search_respects_region_and_limit() {
+        assert_eq!(
+            names(&search_realms(None, "storm", 4)),
+            ["Stormrage-us", "Stormrage-eu", "Stormreaver-eu", "Stormscale-us"]
+        );
+        assert_eq!(
+            names(&search_realms(Some(Region::Eu), "storm", 10)),
+            ["Stormrage-eu", "Stormreaver-eu", "Stormscale-eu"]
+        );
+        assert!(search_realms(Some(Region::Us), "storm", 0).is_empty());
+        assert!(search_realms(None, "zzzz", 10).is_empty());
+    }
+
+    #[test]
+    fn empty_query_lists_realms_alphabetically() {
+        assert_eq!(
+            names(&search_realms(Some(Region::Us), "", 3)),
+            ["Aegwynn-us", "Aerie Peak-us", "Agamaggan-us"]
+        );
+        assert_eq!(search_realms(Some(Region::Us), " '- ", 3).len(), 3);
+    }
+
+    #[test]
+    fn normalized_realm_names() {
+        assert_eq!(normalize_realm("area52"), "Area 52");
+        assert_eq!(normalize_realm("wyrmrest-accord"), "Wyrmrest Accord");
+        // not in the catalogue: slugs are title cased back, anything else kept as typed.
+        assert_eq!(normalize_realm("twisting-nether-pvp"), "Twisting Nether Pvp");
+        assert_eq!(normalize_realm("  Some  Realm "), "Some Realm");
+    }
+
+    #[test]
+    fn catalogue_realms_use_blizzards_slug() {
+        assert_eq!(realm_slug("Aggra (Português)"), "aggra-portugues");
+        assert_eq!(realm_slug("mal'ganis"), "malganis");
+        assert_eq!(realm_slug("area-52"), "area-52");
+    }
+
+    #[test]
+    fn other_realms_are_folded_the_same_way() {
+        assert_eq!(realm_slug("Séance (Français)"), "seance-francais");
+        assert_eq!(realm_slug("  Kel’Thuzad's   Rest "), "kelthuzads-rest");
+        assert_eq!(realm_slug("Chants Éternels-Nord"), "chants-eternels-nord");
+        assert_eq!(realm_slug("chants-eternels-nord"), "chants-eternels-nord");
+    }
+}
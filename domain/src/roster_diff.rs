@@ -0,0 +1,202 @@
+use serde::Serialize;
+
+use crate::member::MemberDocument;
+use crate::player_metadata::{build_roster, MetadataError, Player, RosterDocument};
+
+// +-------------+
+// | Roster Diff |
+// +-------------+
+// Who joined, who left and who changed role or class between two season rosters (ex: Gallywix
+// to Dimensius). Players are matched by character first, then by member (members.json), so a
+// raider bringing an alt counts as a class swap rather than a departure and a join.
+
+/// A player on one roster only.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffEntry {
+    /// the member's display name, when members.json knows the character.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    pub player: Player,
+}
+
+/// The same character or member on both rosters.
+#[derive(Debug, Clone, Serialize)]
+pub struct Swap {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    pub before: Player,
+    pub after: Player,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RosterDiff {
+    pub from: String,
+    pub to: String,
+    /// on `to` only, in `to`'s order.
+    pub joined: Vec<DiffEntry>,
+    /// on `from` only, in `from`'s order.
+    pub left: Vec<DiffEntry>,
+    pub role_swaps: Vec<Swap>,
+    /// a different class, usually a member switching characters.
+    pub class_swaps: Vec<Swap>,
+    /// on both with the same class and role.
+    pub unchanged: usize,
+}
+
+impl RosterDiff {
+    /// Compare two rosters of a rosters.json document by id.
+    pub fn between(doc: &RosterDocument, members: &MemberDocument, from: &str, to: &str) -> Result<Self, MetadataError> {
+        let before = build_roster(doc, from)?;
+        let after = build_roster(doc, to)?;
+        Ok(Self::compare(members, from, &before, to, &after))
+    }
+
+    pub fn compare(members: &MemberDocument, from: &str, before: &[Player], to: &str, after: &[Player]) -> Self {
+        let member_name = |player: &Player| members.for_player(player).map(|member| member.display_name.clone());
+        let same_member = |a: &Player, b: &Player| match (members.for_player(a), members.for_player(b)) {
+            (Some(a), Some(b)) => a.id == b.id,
+            _ => false,
+        };
+
+        // characters first, so a member with two characters on a roster keeps the right pairs.
+        let mut remaining: Vec<&Player> = after.iter().collect();
+        let mut pairs = Vec::new();
+        let mut unmatched = Vec::new();
+        for player in before {
+            match remaining.iter().position(|other| other.is(&player.name, &player.realm, player.region)) {
+                Some(i) => pairs.push((player, remaining.remove(i))),
+                None => unmatched.push(player),
+            }
+        }
+        let mut left = Vec::new();
+        for player in unmatched {
+            match remaining.iter().position(|other| same_member(player, other)) {
+                Some(i) => pairs.push((player, remaining.remove(i))),
+                None => left.push(DiffEntry { member: member_name(player), player: player.clone() }),
+            }
+        }
+        let joined = remaining
+            .into_iter()
+            .map(|player| DiffEntry { member: member_name(player), player: player.clone() })
+            .collect();
+
+        // keep `from`'s order for swaps too.
+        pairs.sort_by_key(|(player, _)| before.iter().position(|other| std::ptr::eq(other, *player)));
+        let mut role_swaps = Vec::new();
+        let mut class_swaps = Vec::new();
+        let mut unchanged = 0;
+        for (before, after) in pairs {
+            let swap = || Swap { member: member_name(before), before: before.clone(), after: after.clone() };
+            if before.class != after.class {
+                class_swaps.push(swap());
+            }
+            if before.role != after.role {
+                role_swaps.push(swap());
+            }
+            if before.class == after.class && before.role == after.role {
+                unchanged += 1;
+            }
+        }
+
+        RosterDiff {
+            from: from.to_string(),
+            to: to.to_string(),
+            joined,
+            left,
+            role_swaps,
+            class_swaps,
+            unchanged,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.joined.is_empty() && self.left.is_empty() && self.role_swaps.is_empty() && self.class_swaps.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wow::PlayerRole;
+
+    // the bundled copies the worker falls back to.
+    fn documents() -> (RosterDocument, MemberDocument) {
+        let rosters = RosterDocument::from_json(include_str!("../../templates/assets/rosters.json")).unwrap();
+        let members = MemberDocument::from_json(include_str!("../../templates/assets/members.json")).unwrap();
+        (rosters, members)
+    }
+
+    fn names(entries: &[DiffEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.player.name.as_str()).collect()
+    }
+
+    fn swapped(swaps: &[Swap]) -> Vec<(&str, &str)> {
+        swaps
+            .iter()
+            .map(|swap| (swap.before.name.as_str(), swap.after.name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn gallywix_to_dimensius() {
+        let (rosters, members) = documents();
+        let diff = RosterDiff::between(&rosters, &members, "Gallywix", "Dimensius").unwrap();
+
+        assert_eq!(names(&diff.left), ["Jaemsy", "Oldmanzand", "Jedh", "Notshodo", "Filio", "Dubshamm"]);
+        assert_eq!(
+            names(&diff.joined),
+            ["Purpformance", "Philfishh", "Fliptwisty", "Ovtlaw", "Prankdaddy", "Purpleheal"]
+        );
+        // alts of the same member, matched by members.json.
+        assert_eq!(
+            swapped(&diff.class_swaps),
+            [("Pipmeow", "Piptide"), ("Rogergrowth", "Rogerport"), ("Ppdx", "Ppddk")]
+        );
+        assert_eq!(diff.class_swaps[0].member.as_deref(), Some("Pip"));
+        assert!(diff.role_swaps.is_empty());
+        assert_eq!(diff.unchanged, 11);
+    }
+
+    #[test]
+    fn kyvesa_to_gallywix() {
+        let (rosters, members) = documents();
+        let diff = RosterDiff::between(&rosters, &members, "Kyvesa", "Gallywix").unwrap();
+
+        assert_eq!(swapped(&diff.role_swaps), [("Notshodo", "Notshodo")]);
+        assert_eq!(diff.role_swaps[0].before.role, PlayerRole::Healer);
+        assert_eq!(diff.role_swaps[0].after.role, PlayerRole::Dps);
+        // Kolzane moved from Stormrage to Ysera and Delusionil became Delusionol: same member,
+        // neither a departure nor a join.
+        for name in ["Kolzane", "Delusionil"] {
+            assert!(!names(&diff.left).contains(&name), "{} should not have left", name);
+        }
+        for name in ["Kolzane", "Delusionol"] {
+            assert!(!names(&diff.joined).contains(&name), "{} should not have joined", name);
+        }
+        assert_eq!(
+            diff.unchanged + diff.role_swaps.len() + diff.class_swaps.len() + diff.left.len(),
+            rosters.rosters["Kyvesa"].len()
+        );
+        assert_eq!(
+            diff.unchanged + diff.role_swaps.len() + diff.class_swaps.len() + diff.joined.len(),
+            rosters.rosters["Gallywix"].len()
+        );
+    }
+
+    #[test]
+    fn same_roster_has_no_changes() {
+        let (rosters, members) = documents();
+        let diff = RosterDiff::between(&rosters, &members, "Fyrakk", "Fyrakk").unwrap();
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, rosters.rosters["Fyrakk"].len());
+    }
+
+    #[test]
+    fn unknown_roster() {
+        let (rosters, members) = documents();
+        let diff = RosterDiff::between(&rosters, &members, "Gallywix", "Ragnaros");
+
+        assert!(matches!(diff, Err(MetadataError::UnknownRoster(id)) if id == "Ragnaros"));
+    }
+}
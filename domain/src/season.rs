@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::player_metadata::{check_version, parse_document, MetadataError};
+use crate::time::Region;
+
+// +---------------+
+// | Season Config |
+// +---------------+
+// Expansions, seasons, raid zones, bosses and the M+ dungeon pool.
+// Lives in R2 as content/seasons.json (bundled copy in templates/assets/seasons.json), loaded by the worker's season.rs.
+// Rolling over to a new season: add it to "seasons" and point "current_season" at it.
+
+/// bump when the layout of seasons.json changes.
+pub const SEASONS_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeasonConfig {
+    pub version: u32,
+    /// id of the season the site is currently tracking (ex: "tww-s3").
+    pub current_season: String,
+    pub expansions: Vec<Expansion>,
+    pub seasons: Vec<Season>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expansion {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Season {
+    pub id: String,
+    /// ex: "Season 3"
+    pub name: String,
+    /// id of the expansion this season belongs to.
+    pub expansion: String,
+    /// US launch date (EU is the day after).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts: Option<NaiveDate>,
+    /// last day of the season, unset while it's running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends: Option<NaiveDate>,
+    pub raids: Vec<RaidZone>,
+    /// keystone dungeon pool, only needed while the season is current.
+    #[serde(default)]
+    pub dungeons: Vec<Dungeon>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RaidZone {
+    pub name: String,
+    /// warcraftlogs zone id, used for the guild progress link.
+    #[serde(default)]
+    pub wcl_zone: Option<u32>,
+    /// in kill order, last boss last.
+    pub bosses: Vec<Boss>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Boss {
+    pub name: String,
+    pub icon_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dungeon {
+    /// blizzard's keystone dungeon id (ex: 503 is Ara-Kara).
+    pub id: u32,
+    pub name: String,
+    pub icon_url: String,
+}
+
+const WCL_GUILD_ID: u32 = 289615;
+
+impl Season {
+    /// "week 3" of the season in `region`, None before it starts, after it ends, or when
+    /// seasons.json has no start date.
+    pub fn week(&self, region: Region, now: DateTime<Utc>) -> Option<u32> {
+        if self.ends.is_some_and(|ends| now.date_naive() > ends) {
+            return None;
+        }
+        region.season_week(self.starts?, now)
+    }
+}
+
+impl RaidZone {
+    pub fn wcl_progress_url(&self) -> Option<String> {
+        self.wcl_zone.map(|zone| {
+            format!(
+                "https://www.warcraftlogs.com/guild/progress/{}?zone={}",
+                WCL_GUILD_ID, zone
+            )
+        })
+    }
+}
+
+impl SeasonConfig {
+    /// parse and validate a seasons.json document.
+    pub fn from_json(json: &str) -> Result<Self, MetadataError> {
+        let config: SeasonConfig = parse_document("seasons.json", json)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), MetadataError> {
+        check_version("seasons.json", self.version, SEASONS_SCHEMA_VERSION)?;
+        let invalid = |item: &str, reason: &str| MetadataError::Invalid {
+            item: item.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let mut seen = HashSet::new();
+        for season in &self.seasons {
+            if !seen.insert(season.id.as_str()) {
+                return Err(invalid(&season.id, "season id is used twice"));
+            }
+            if self.expansion(&season.expansion).is_none() {
+                return Err(invalid(&season.id, "season's expansion is not listed in expansions"));
+            }
+            if let (Some(starts), Some(ends)) = (season.starts, season.ends) {
+                if ends < starts {
+                    return Err(invalid(&season.id, "season ends before it starts"));
+                }
+            }
+        }
+        if self.season(&self.current_season).is_none() {
+            return Err(invalid(&self.current_season, "current_season is not listed in seasons"));
+        }
+        Ok(())
+    }
+
+    pub fn season(&self, id: &str) -> Option<&Season> {
+        self.seasons.iter().find(|season| season.id == id)
+    }
+
+    pub fn expansion(&self, id: &str) -> Option<&Expansion> {
+        self.expansions.iter().find(|expansion| expansion.id == id)
+    }
+
+    /// the season the site is tracking. validate() guarantees it exists.
+    pub fn current(&self) -> &Season {
+        self.season(&self.current_season)
+            .expect("current_season is checked when seasons.json is loaded")
+    }
+
+    /// the current season's newest raid.
+    pub fn current_raid(&self) -> Option<&RaidZone> {
+        self.current().raids.last()
+    }
+
+    /// a dungeon from the current season's pool.
+    pub fn dungeon(&self, id: u32) -> Option<&Dungeon> {
+        self.current().dungeons.iter().find(|dungeon| dungeon.id == id)
+    }
+
+    /// "Season 3 of The War Within"
+    pub fn season_label(&self, id: &str) -> String {
+        match self.season(id) {
+            Some(season) => match self.expansion(&season.expansion) {
+                Some(expansion) => format!("{} of {}", season.name, expansion.name),
+                None => season.name.clone(),
+            },
+            None => id.to_string(),
+        }
+    }
+}
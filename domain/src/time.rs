@@ -0,0 +1,499 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+// +--------------+
+// | WoW Calendar |
+// +--------------+
+// Weekly resets, raid nights and timezone formatting. Nothing here reads a clock, callers pass
+// `now` in (the worker's Utc::now() is Date.now() under wasm), so every answer is reproducible.
+
+// +--------+
+// | Region |
+// +--------+
+
+/// written "us" / "eu" in documents and urls. the guild is on us realms, so that's the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Region {
+    #[default]
+    Us,
+    Eu,
+}
+
+impl Region {
+    pub const ALL: [Region; 2] = [Region::Us, Region::Eu];
+
+    pub fn is_us(&self) -> bool {
+        *self == Region::Us
+    }
+
+    /// "US" / "EU"
+    pub fn label(&self) -> &'static str {
+        match self {
+            Region::Us => "US",
+            Region::Eu => "EU",
+        }
+    }
+
+    /// How a realm on this region is shown: "Stormrage" on the guild's region (US), and
+    /// "Stormrage (EU)" anywhere else.
+    pub fn realm_label(&self, realm: &str) -> String {
+        match self {
+            Region::Us => realm.to_string(),
+            region => format!("{} ({})", realm, region.label()),
+        }
+    }
+
+    /// weekday and UTC hour of the weekly reset. Blizzard keeps these in UTC all year.
+    fn reset(&self) -> (Weekday, u32) {
+        match self {
+            Region::Us => (Weekday::Tue, 15),
+            Region::Eu => (Weekday::Wed, 4),
+        }
+    }
+
+    /// the most recent weekly reset at or before `now`.
+    pub fn last_reset(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let (weekday, hour) = self.reset();
+        let days_since = (now.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        let reset = at_utc(now.date_naive() - Duration::days(days_since as i64), hour);
+        match reset > now {
+            // reset day, before the reset.
+            true => reset - Duration::weeks(1),
+            false => reset,
+        }
+    }
+
+    pub fn next_reset(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.last_reset(now) + Duration::weeks(1)
+    }
+
+    /// 1 for the week a season starts in, counted in this region's resets. `starts` is the US
+    /// launch date (seasons.json), EU launches the day after. None before the season has started.
+    pub fn season_week(&self, starts: NaiveDate, now: DateTime<Utc>) -> Option<u32> {
+        let launch = match self {
+            Region::Us => starts,
+            Region::Eu => starts + Duration::days(1),
+        };
+        let first_reset = self.last_reset(at_utc(launch, 23));
+        if now < first_reset {
+            return None;
+        }
+        Some((self.last_reset(now) - first_reset).num_weeks() as u32 + 1)
+    }
+}
+
+impl std::fmt::Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Region::Us => write!(f, "us"),
+            Region::Eu => write!(f, "eu"),
+        }
+    }
+}
+
+impl std::str::FromStr for Region {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "us" | "na" => Ok(Region::Us),
+            "eu" => Ok(Region::Eu),
+            _ => Err(format!("unknown region: {}", value)),
+        }
+    }
+}
+
+fn at_utc(date: NaiveDate, hour: u32) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(hour, 0, 0).expect("hour is below 24"))
+}
+
+// +-----------+
+// | Timezones |
+// +-----------+
+// The handful of zones raiders are in, with their daylight saving rules written out so the
+// worker doesn't need a tz database.
+
+/// where the guild raids from, kill times and raid nights are shown in it.
+pub const GUILD_ZONE: Zone = Zone::Eastern;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Zone {
+    Utc,
+    Eastern,
+    Central,
+    Mountain,
+    Pacific,
+    /// London
+    UkTime,
+    /// Paris, Berlin, Stockholm...
+    CentralEurope,
+}
+
+impl Zone {
+    pub const ALL: [Zone; 7] = [
+        Zone::Utc,
+        Zone::Eastern,
+        Zone::Central,
+        Zone::Mountain,
+        Zone::Pacific,
+        Zone::UkTime,
+        Zone::CentralEurope,
+    ];
+
+    fn standard_hours(&self) -> i32 {
+        match self {
+            Zone::Utc | Zone::UkTime => 0,
+            Zone::Eastern => -5,
+            Zone::Central => -6,
+            Zone::Mountain => -7,
+            Zone::Pacific => -8,
+            Zone::CentralEurope => 1,
+        }
+    }
+
+    // (standard, daylight)
+    fn abbreviations(&self) -> (&'static str, &'static str) {
+        match self {
+            Zone::Utc => ("UTC", "UTC"),
+            Zone::Eastern => ("EST", "EDT"),
+            Zone::Central => ("CST", "CDT"),
+            Zone::Mountain => ("MST", "MDT"),
+            Zone::Pacific => ("PST", "PDT"),
+            Zone::UkTime => ("GMT", "BST"),
+            Zone::CentralEurope => ("CET", "CEST"),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Zone::Utc => "UTC",
+            Zone::Eastern => "Eastern",
+            Zone::Central => "Central",
+            Zone::Mountain => "Mountain",
+            Zone::Pacific => "Pacific",
+            Zone::UkTime => "UK",
+            Zone::CentralEurope => "Central European",
+        }
+    }
+
+    /// whether daylight saving time is in effect at `utc`.
+    pub fn is_daylight(&self, utc: DateTime<Utc>) -> bool {
+        let year = utc.year();
+        let (starts, ends) = match self {
+            Zone::Utc => return false,
+            // second Sunday of March 2:00 local standard time, to the first Sunday of November
+            // 2:00 local daylight time.
+            Zone::Eastern | Zone::Central | Zone::Mountain | Zone::Pacific => {
+                let standard = -self.standard_hours() as i64;
+                (
+                    at_utc(nth_weekday(year, 3, Weekday::Sun, 2), 0) + Duration::hours(2 + standard),
+                    at_utc(nth_weekday(year, 11, Weekday::Sun, 1), 0) + Duration::hours(1 + standard),
+                )
+            }
+            // last Sunday of March to the last Sunday of October, both at 01:00 UTC.
+            Zone::UkTime | Zone::CentralEurope => (
+                at_utc(last_weekday(year, 3, Weekday::Sun), 1),
+                at_utc(last_weekday(year, 10, Weekday::Sun), 1),
+            ),
+        };
+        utc >= starts && utc < ends
+    }
+
+    pub fn offset_at(&self, utc: DateTime<Utc>) -> FixedOffset {
+        let hours = self.standard_hours() + i32::from(self.is_daylight(utc));
+        FixedOffset::east_opt(hours * 3600).expect("offsets are within a day")
+    }
+
+    /// "EST" or "EDT", whichever applies at `utc`.
+    pub fn abbreviation_at(&self, utc: DateTime<Utc>) -> &'static str {
+        let (standard, daylight) = self.abbreviations();
+        match self.is_daylight(utc) {
+            true => daylight,
+            false => standard,
+        }
+    }
+
+    pub fn to_local(&self, utc: DateTime<Utc>) -> DateTime<FixedOffset> {
+        utc.with_timezone(&self.offset_at(utc))
+    }
+
+    /// strftime in this zone, `%Z` is the zone's abbreviation (ex: "EDT").
+    pub fn format(&self, utc: DateTime<Utc>, format: &str) -> String {
+        let format = format.replace("%Z", self.abbreviation_at(utc));
+        self.to_local(utc).format(&format).to_string()
+    }
+
+    /// a date and time on this zone's clocks, in UTC.
+    pub fn from_local(&self, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+        let naive = date.and_time(time);
+        // which offset applies is decided at the standard time reading, close enough for the
+        // hour a year that is skipped or repeated.
+        let standard = Utc.from_utc_datetime(&naive) - Duration::hours(self.standard_hours() as i64);
+        let offset = self.offset_at(standard);
+        Utc.from_utc_datetime(&(naive - Duration::seconds(offset.local_minus_utc() as i64)))
+    }
+}
+
+impl std::fmt::Display for Zone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// accepts our names, abbreviations and the iana names people actually have.
+impl std::str::FromStr for Zone {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "utc" | "gmt" | "etc/utc" => Ok(Zone::Utc),
+            "eastern" | "est" | "edt" | "et" | "america/new_york" => Ok(Zone::Eastern),
+            "central" | "cst" | "cdt" | "ct" | "america/chicago" => Ok(Zone::Central),
+            "mountain" | "mst" | "mdt" | "mt" | "america/denver" => Ok(Zone::Mountain),
+            "pacific" | "pst" | "pdt" | "pt" | "america/los_angeles" => Ok(Zone::Pacific),
+            "uk" | "bst" | "europe/london" => Ok(Zone::UkTime),
+            "cet" | "cest" | "europe/paris" | "europe/berlin" | "europe/stockholm" => Ok(Zone::CentralEurope),
+            _ => Err(format!("unknown timezone: {}", value)),
+        }
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("every month has 4 of each weekday")
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5).unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+// +-------------+
+// | Raid Nights |
+// +-------------+
+
+#[derive(Debug, Clone, Copy)]
+pub struct RaidNight {
+    pub weekday: Weekday,
+    /// local to the schedule's zone.
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct RaidSchedule {
+    pub zone: Zone,
+    pub nights: Vec<RaidNight>,
+}
+
+impl RaidSchedule {
+    /// Tuesdays and Thursdays, 8 - 11pm Eastern.
+    pub fn guild() -> Self {
+        let night = |weekday| RaidNight {
+            weekday,
+            start: NaiveTime::from_hms_opt(20, 0, 0).expect("valid time"),
+            end: NaiveTime::from_hms_opt(23, 0, 0).expect("valid time"),
+        };
+        RaidSchedule {
+            zone: GUILD_ZONE,
+            nights: vec![night(Weekday::Tue), night(Weekday::Thu)],
+        }
+    }
+
+    /// The raid in progress at `now`, or the next one. (start, end) in UTC.
+    pub fn next_raid(&self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let today = self.zone.to_local(now).date_naive();
+        (0..8)
+            .map(|days| today + Duration::days(days))
+            .flat_map(|date| {
+                self.nights
+                    .iter()
+                    .filter(move |night| night.weekday == date.weekday())
+                    .map(move |night| (self.zone.from_local(date, night.start), self.zone.from_local(date, night.end)))
+            })
+            .filter(|(_, end)| *end > now)
+            .min_by_key(|(start, _)| *start)
+    }
+
+    pub fn is_raiding(&self, now: DateTime<Utc>) -> bool {
+        self.next_raid(now).is_some_and(|(start, _)| start <= now)
+    }
+
+    /// "Tuesdays and Thursdays from 8:00 PM - 11:00 PM EST", abbreviation as of `now`.
+    pub fn label(&self, now: DateTime<Utc>) -> String {
+        let days: Vec<String> = self.nights.iter().map(|night| format!("{}s", weekday_name(night.weekday))).collect();
+        let (start, end) = match self.nights.first() {
+            Some(night) => (night.start, night.end),
+            None => return "No raid nights scheduled".to_string(),
+        };
+        format!(
+            "{} from {} - {} {}",
+            days.join(" and "),
+            start.format("%-I:%M %p"),
+            end.format("%-I:%M %p"),
+            self.zone.abbreviation_at(now)
+        )
+    }
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn us_reset_is_tuesday_1500_utc() {
+        // 2025-10-14 is a Tuesday.
+        assert_eq!(Region::Us.last_reset(utc("2025-10-14T14:59:59Z")), utc("2025-10-07T15:00:00Z"));
+        assert_eq!(Region::Us.last_reset(utc("2025-10-14T15:00:00Z")), utc("2025-10-14T15:00:00Z"));
+        assert_eq!(Region::Us.next_reset(utc("2025-10-14T14:59:59Z")), utc("2025-10-14T15:00:00Z"));
+        assert_eq!(Region::Us.last_reset(utc("2025-10-20T23:00:00Z")), utc("2025-10-14T15:00:00Z"));
+    }
+
+    #[test]
+    fn eu_reset_is_wednesday_0400_utc() {
+        assert_eq!(Region::Eu.last_reset(utc("2025-10-15T03:59:59Z")), utc("2025-10-08T04:00:00Z"));
+        assert_eq!(Region::Eu.last_reset(utc("2025-10-15T04:00:00Z")), utc("2025-10-15T04:00:00Z"));
+        // the US has already reset, EU hasn't.
+        assert_eq!(Region::Eu.last_reset(utc("2025-10-14T16:00:00Z")), utc("2025-10-08T04:00:00Z"));
+    }
+
+    #[test]
+    fn us_daylight_saving() {
+        // 2025: March 9 to November 2, at 2am local.
+        assert!(!Zone::Eastern.is_daylight(utc("2025-03-09T06:59:00Z")));
+        assert!(Zone::Eastern.is_daylight(utc("2025-03-09T07:00:00Z")));
+        assert!(Zone::Eastern.is_daylight(utc("2025-11-02T05:59:00Z")));
+        assert!(!Zone::Eastern.is_daylight(utc("2025-11-02T06:00:00Z")));
+        assert!(!Zone::Pacific.is_daylight(utc("2025-03-09T09:59:00Z")));
+        assert!(Zone::Pacific.is_daylight(utc("2025-03-09T10:00:00Z")));
+        // November 1st 2026 is itself the first Sunday.
+        assert!(Zone::Eastern.is_daylight(utc("2026-11-01T05:59:00Z")));
+        assert!(!Zone::Eastern.is_daylight(utc("2026-11-01T06:00:00Z")));
+        assert_eq!(Zone::Eastern.abbreviation_at(utc("2026-10-31T12:00:00Z")), "EDT");
+        assert_eq!(Zone::Eastern.abbreviation_at(utc("2026-11-02T12:00:00Z")), "EST");
+    }
+
+    #[test]
+    fn eu_daylight_saving() {
+        // 2025: last Sundays of March (30th) and October (26th), at 01:00 UTC.
+        assert!(!Zone::UkTime.is_daylight(utc("2025-03-30T00:59:00Z")));
+        assert!(Zone::UkTime.is_daylight(utc("2025-03-30T01:00:00Z")));
+        assert!(Zone::CentralEurope.is_daylight(utc("2025-10-26T00:59:00Z")));
+        assert!(!Zone::CentralEurope.is_daylight(utc("2025-10-26T01:00:00Z")));
+        assert_eq!(Zone::CentralEurope.format(utc("2025-07-01T18:00:00Z"), "%H:%M %Z"), "20:00 CEST");
+        assert_eq!(Zone::UkTime.format(utc("2025-12-01T18:00:00Z"), "%H:%M %Z"), "18:00 GMT");
+        assert!(!Zone::Utc.is_daylight(utc("2025-07-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn from_local_across_daylight_saving() {
+        assert_eq!(Zone::Eastern.from_local(date("2025-03-08"), time(20, 0)), utc("2025-03-09T01:00:00Z"));
+        assert_eq!(Zone::Eastern.from_local(date("2025-03-10"), time(20, 0)), utc("2025-03-11T00:00:00Z"));
+        assert_eq!(Zone::Eastern.from_local(date("2025-11-01"), time(20, 0)), utc("2025-11-02T00:00:00Z"));
+        assert_eq!(Zone::Eastern.from_local(date("2025-11-03"), time(20, 0)), utc("2025-11-04T01:00:00Z"));
+        assert_eq!(Zone::CentralEurope.from_local(date("2025-10-27"), time(20, 0)), utc("2025-10-27T19:00:00Z"));
+    }
+
+    #[test]
+    fn season_week_counts_resets_from_launch() {
+        // US launches Tuesday 2025-08-12, EU the day after.
+        let starts = date("2025-08-12");
+
+        assert_eq!(Region::Us.season_week(starts, utc("2025-08-12T14:59:00Z")), None);
+        assert_eq!(Region::Us.season_week(starts, utc("2025-08-12T15:00:00Z")), Some(1));
+        assert_eq!(Region::Us.season_week(starts, utc("2025-08-19T14:59:00Z")), Some(1));
+        assert_eq!(Region::Us.season_week(starts, utc("2025-08-19T15:00:00Z")), Some(2));
+        assert_eq!(Region::Us.season_week(starts, utc("2025-10-14T15:00:00Z")), Some(10));
+
+        assert_eq!(Region::Eu.season_week(starts, utc("2025-08-12T15:00:00Z")), None);
+        assert_eq!(Region::Eu.season_week(starts, utc("2025-08-13T03:59:00Z")), None);
+        assert_eq!(Region::Eu.season_week(starts, utc("2025-08-13T04:00:00Z")), Some(1));
+        assert_eq!(Region::Eu.season_week(starts, utc("2025-08-20T04:00:00Z")), Some(2));
+    }
+
+    #[test]
+    fn next_raid_during_a_raid() {
+        let schedule = RaidSchedule::guild();
+        // Tuesday 2025-10-14, 9pm EDT.
+        let now = utc("2025-10-15T01:00:00Z");
+
+        assert_eq!(schedule.next_raid(now), Some((utc("2025-10-15T00:00:00Z"), utc("2025-10-15T03:00:00Z"))));
+        assert!(schedule.is_raiding(now));
+    }
+
+    #[test]
+    fn next_raid_after_the_last_raid_of_the_week() {
+        let schedule = RaidSchedule::guild();
+        // Thursday 2025-10-16, 11:30pm EDT.
+        let now = utc("2025-10-17T03:30:00Z");
+
+        assert_eq!(schedule.next_raid(now), Some((utc("2025-10-22T00:00:00Z"), utc("2025-10-22T03:00:00Z"))));
+        assert!(!schedule.is_raiding(now));
+    }
+
+    #[test]
+    fn next_raid_across_the_daylight_saving_switch() {
+        let schedule = RaidSchedule::guild();
+        // Thursday 2025-10-30 after raid, EDT. The next raid is on EST.
+        let now = utc("2025-10-31T03:30:00Z");
+
+        assert_eq!(schedule.next_raid(now), Some((utc("2025-11-05T01:00:00Z"), utc("2025-11-05T04:00:00Z"))));
+        assert_eq!(schedule.label(now), "Tuesdays and Thursdays from 8:00 PM - 11:00 PM EDT");
+        assert_eq!(schedule.label(utc("2025-11-05T01:00:00Z")), "Tuesdays and Thursdays from 8:00 PM - 11:00 PM EST");
+    }
+
+    #[test]
+    fn no_raid_nights() {
+        let schedule = RaidSchedule { zone: Zone::Eastern, nights: Vec::new() };
+
+        assert_eq!(schedule.next_raid(utc("2025-10-15T01:00:00Z")), None);
+        assert_eq!(schedule.label(utc("2025-10-15T01:00:00Z")), "No raid nights scheduled");
+    }
+
+    #[test]
+    fn kill_times_match_the_old_hand_written_labels() {
+        use crate::player_metadata::RaidManifest;
+        use crate::season::SeasonConfig;
+
+        let seasons = SeasonConfig::from_json(include_str!("../../templates/assets/seasons.json")).unwrap();
+        let raids = RaidManifest::from_json(include_str!("../../templates/assets/raids.json"), &seasons).unwrap();
+        let labels: Vec<(&str, String)> = raids
+            .kills
+            .iter()
+            .map(|kill| (kill.fight_name.as_str(), kill.pretty_datetime()))
+            .collect();
+
+        assert_eq!(
+            labels,
+            [
+                ("Dimensius", "10:45pm - 18 December 2025".to_string()),
+                ("Gallywix", "9:13pm - 26 June 2025".to_string()),
+                ("Kyvesa", "10:15pm - 12 December 2024".to_string()),
+                ("Fyrakk", "10:03pm - 1 July 2024".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,568 @@
+use serde::{Deserialize, Serialize};
+
+// +------------+
+// | WoW Domain |
+// +------------+
+// Classes, specs and roles shared by every page (rosters, sims, keys).
+
+// lower case and drop spaces/dashes/underscores/apostrophes so "Death Knight", "death-knight"
+// and "DeathKnight" all compare equal.
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// +------+
+// | Role |
+// +------+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum PlayerRole {
+    Tank,
+    Healer,
+    Dps,
+}
+
+// map a PlayerRole to a known icon url.
+impl PlayerRole {
+    pub fn icon_url(&self) -> &'static str {
+        match self {
+            PlayerRole::Tank => "https://r2.seemsgood.org/content/icons/tank.png",
+            PlayerRole::Healer => "https://r2.seemsgood.org/content/icons/healer.png",
+            PlayerRole::Dps => "https://r2.seemsgood.org/content/icons/dps.png",
+        }
+    }
+}
+
+// map a PlayerRole to its string value so 'player.role.tank' would format to 'tank' string.
+// if a icon url cant resolve or render on a device, it will default to
+impl std::fmt::Display for PlayerRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let role_str = match self {
+            PlayerRole::Tank => "Tank",
+            PlayerRole::Healer => "Healer",
+            PlayerRole::Dps => "Dps",
+        };
+        write!(f, "{}", role_str)
+    }
+}
+
+// accepts our names plus the ones wowaudit ("Heal", "Melee", "Ranged") and raider.io ("healing") use.
+impl std::str::FromStr for PlayerRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match normalize(value).as_str() {
+            "tank" => Ok(PlayerRole::Tank),
+            "healer" | "heal" | "healing" => Ok(PlayerRole::Healer),
+            "dps" | "damage" | "melee" | "ranged" => Ok(PlayerRole::Dps),
+            _ => Err(format!("unknown role: {}", value)),
+        }
+    }
+}
+
+// +-------+
+// | Class |
+// +-------+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum PlayerClass {
+    Warrior,
+    Mage,
+    Rogue,
+    Hunter,
+    Druid,
+    Paladin,
+    Priest,
+    Warlock,
+    Monk,
+    DeathKnight,
+    Shaman,
+    DemonHunter,
+    Evoker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmorType {
+    Cloth,
+    Leather,
+    Mail,
+    Plate,
+}
+
+impl std::fmt::Display for ArmorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let armor_str = match self {
+            ArmorType::Cloth => "Cloth",
+            ArmorType::Leather => "Leather",
+            ArmorType::Mail => "Mail",
+            ArmorType::Plate => "Plate",
+        };
+        write!(f, "{}", armor_str)
+    }
+}
+
+impl PlayerClass {
+    /// every class, alphabetical (same order as the class buttons on /keys).
+    pub const ALL: [PlayerClass; 13] = [
+        PlayerClass::DeathKnight,
+        PlayerClass::DemonHunter,
+        PlayerClass::Druid,
+        PlayerClass::Evoker,
+        PlayerClass::Hunter,
+        PlayerClass::Mage,
+        PlayerClass::Monk,
+        PlayerClass::Paladin,
+        PlayerClass::Priest,
+        PlayerClass::Rogue,
+        PlayerClass::Shaman,
+        PlayerClass::Warlock,
+        PlayerClass::Warrior,
+    ];
+
+    pub fn rgb(&self) -> &'static str {
+        match self {
+            PlayerClass::DeathKnight => "rgb(196, 30, 58)",
+            PlayerClass::DemonHunter => "rgb(163, 48, 201)",
+            PlayerClass::Druid => "rgb(255, 124, 10)",
+            PlayerClass::Evoker => "rgb(51, 147, 127)",
+            PlayerClass::Hunter => "rgb(170, 211, 114)",
+            PlayerClass::Mage => "rgb(63, 199, 235)",
+            PlayerClass::Monk => "rgb(0, 255, 152)",
+            PlayerClass::Paladin => "rgb(244, 140, 186)",
+            PlayerClass::Priest => "rgb(255, 255, 255)",
+            PlayerClass::Rogue => "rgb(255, 244, 104)",
+            PlayerClass::Shaman => "rgb(0, 112, 221)",
+            PlayerClass::Warlock => "rgb(135, 136, 238)",
+            PlayerClass::Warrior => "rgb(198, 155, 109)",
+        }
+    }
+
+    /// rgb() as 0xRRGGBB, the way discord embeds take colours.
+    pub fn color_value(&self) -> u32 {
+        self.rgb()
+            .trim_start_matches("rgb(")
+            .trim_end_matches(')')
+            .split(',')
+            .filter_map(|channel| channel.trim().parse::<u32>().ok())
+            .fold(0, |color, channel| (color << 8) | channel)
+    }
+
+    /// "Death Knight", for labels. Display gives "DeathKnight" to match our json.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PlayerClass::DeathKnight => "Death Knight",
+            PlayerClass::DemonHunter => "Demon Hunter",
+            PlayerClass::Druid => "Druid",
+            PlayerClass::Evoker => "Evoker",
+            PlayerClass::Hunter => "Hunter",
+            PlayerClass::Mage => "Mage",
+            PlayerClass::Monk => "Monk",
+            PlayerClass::Paladin => "Paladin",
+            PlayerClass::Priest => "Priest",
+            PlayerClass::Rogue => "Rogue",
+            PlayerClass::Shaman => "Shaman",
+            PlayerClass::Warlock => "Warlock",
+            PlayerClass::Warrior => "Warrior",
+        }
+    }
+
+    pub fn armor_type(&self) -> ArmorType {
+        match self {
+            PlayerClass::Mage | PlayerClass::Priest | PlayerClass::Warlock => ArmorType::Cloth,
+            PlayerClass::DemonHunter | PlayerClass::Druid | PlayerClass::Monk | PlayerClass::Rogue => {
+                ArmorType::Leather
+            }
+            PlayerClass::Evoker | PlayerClass::Hunter | PlayerClass::Shaman => ArmorType::Mail,
+            PlayerClass::DeathKnight | PlayerClass::Paladin | PlayerClass::Warrior => ArmorType::Plate,
+        }
+    }
+
+    pub fn icon_url(&self) -> String {
+        format!(
+            "https://wow.zamimg.com/images/wow/icons/large/classicon_{}.jpg",
+            self.to_string().to_lowercase()
+        )
+    }
+
+    pub fn specs(&self) -> Vec<Spec> {
+        Spec::ALL.iter().copied().filter(|spec| spec.class() == *self).collect()
+    }
+}
+
+impl std::fmt::Display for PlayerClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let class_str = match self {
+            PlayerClass::DeathKnight => "DeathKnight",
+            PlayerClass::DemonHunter => "DemonHunter",
+            PlayerClass::Druid => "Druid",
+            PlayerClass::Evoker => "Evoker",
+            PlayerClass::Hunter => "Hunter",
+            PlayerClass::Mage => "Mage",
+            PlayerClass::Monk => "Monk",
+            PlayerClass::Paladin => "Paladin",
+            PlayerClass::Priest => "Priest",
+            PlayerClass::Rogue => "Rogue",
+            PlayerClass::Shaman => "Shaman",
+            PlayerClass::Warlock => "Warlock",
+            PlayerClass::Warrior => "Warrior",
+        };
+        write!(f, "{}", class_str)
+    }
+}
+
+// "DeathKnight", "Death Knight" and "death-knight" all parse.
+impl std::str::FromStr for PlayerClass {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value_key = normalize(value);
+        PlayerClass::ALL
+            .into_iter()
+            .find(|class| normalize(&class.to_string()) == value_key)
+            .ok_or_else(|| format!("unknown class: {}", value))
+    }
+}
+
+// +------+
+// | Spec |
+// +------+
+// Spec names shared by two classes (Frost, Holy, Protection, Restoration) carry the class in the variant.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Spec {
+    Blood,
+    FrostDeathKnight,
+    Unholy,
+    Havoc,
+    Vengeance,
+    Balance,
+    Feral,
+    Guardian,
+    RestorationDruid,
+    Augmentation,
+    Devastation,
+    Preservation,
+    BeastMastery,
+    Marksmanship,
+    Survival,
+    Arcane,
+    Fire,
+    FrostMage,
+    Brewmaster,
+    Mistweaver,
+    Windwalker,
+    HolyPaladin,
+    ProtectionPaladin,
+    Retribution,
+    Discipline,
+    HolyPriest,
+    Shadow,
+    Assassination,
+    Outlaw,
+    Subtlety,
+    Elemental,
+    Enhancement,
+    RestorationShaman,
+    Affliction,
+    Demonology,
+    Destruction,
+    Arms,
+    Fury,
+    ProtectionWarrior,
+}
+
+impl Spec {
+    pub const ALL: [Spec; 39] = [
+        Spec::Blood,
+        Spec::FrostDeathKnight,
+        Spec::Unholy,
+        Spec::Havoc,
+        Spec::Vengeance,
+        Spec::Balance,
+        Spec::Feral,
+        Spec::Guardian,
+        Spec::RestorationDruid,
+        Spec::Augmentation,
+        Spec::Devastation,
+        Spec::Preservation,
+        Spec::BeastMastery,
+        Spec::Marksmanship,
+        Spec::Survival,
+        Spec::Arcane,
+        Spec::Fire,
+        Spec::FrostMage,
+        Spec::Brewmaster,
+        Spec::Mistweaver,
+        Spec::Windwalker,
+        Spec::HolyPaladin,
+        Spec::ProtectionPaladin,
+        Spec::Retribution,
+        Spec::Discipline,
+        Spec::HolyPriest,
+        Spec::Shadow,
+        Spec::Assassination,
+        Spec::Outlaw,
+        Spec::Subtlety,
+        Spec::Elemental,
+        Spec::Enhancement,
+        Spec::RestorationShaman,
+        Spec::Affliction,
+        Spec::Demonology,
+        Spec::Destruction,
+        Spec::Arms,
+        Spec::Fury,
+        Spec::ProtectionWarrior,
+    ];
+
+    pub fn class(&self) -> PlayerClass {
+        match self {
+            Spec::Blood | Spec::FrostDeathKnight | Spec::Unholy => PlayerClass::DeathKnight,
+            Spec::Havoc | Spec::Vengeance => PlayerClass::DemonHunter,
+            Spec::Balance | Spec::Feral | Spec::Guardian | Spec::RestorationDruid => PlayerClass::Druid,
+            Spec::Augmentation | Spec::Devastation | Spec::Preservation => PlayerClass::Evoker,
+            Spec::BeastMastery | Spec::Marksmanship | Spec::Survival => PlayerClass::Hunter,
+            Spec::Arcane | Spec::Fire | Spec::FrostMage => PlayerClass::Mage,
+            Spec::Brewmaster | Spec::Mistweaver | Spec::Windwalker => PlayerClass::Monk,
+            Spec::HolyPaladin | Spec::ProtectionPaladin | Spec::Retribution => PlayerClass::Paladin,
+            Spec::Discipline | Spec::HolyPriest | Spec::Shadow => PlayerClass::Priest,
+            Spec::Assassination | Spec::Outlaw | Spec::Subtlety => PlayerClass::Rogue,
+            Spec::Elemental | Spec::Enhancement | Spec::RestorationShaman => PlayerClass::Shaman,
+            Spec::Affliction | Spec::Demonology | Spec::Destruction => PlayerClass::Warlock,
+            Spec::Arms | Spec::Fury | Spec::ProtectionWarrior => PlayerClass::Warrior,
+        }
+    }
+
+    pub fn default_role(&self) -> PlayerRole {
+        match self {
+            Spec::Blood
+            | Spec::Vengeance
+            | Spec::Guardian
+            | Spec::Brewmaster
+            | Spec::ProtectionPaladin
+            | Spec::ProtectionWarrior => PlayerRole::Tank,
+            Spec::RestorationDruid
+            | Spec::Preservation
+            | Spec::Mistweaver
+            | Spec::HolyPaladin
+            | Spec::Discipline
+            | Spec::HolyPriest
+            | Spec::RestorationShaman => PlayerRole::Healer,
+            _ => PlayerRole::Dps,
+        }
+    }
+
+    pub fn armor_type(&self) -> ArmorType {
+        self.class().armor_type()
+    }
+
+    /// spec name as it shows in game, ex: "Beast Mastery", "Frost".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Spec::Blood => "Blood",
+            Spec::FrostDeathKnight | Spec::FrostMage => "Frost",
+            Spec::Unholy => "Unholy",
+            Spec::Havoc => "Havoc",
+            Spec::Vengeance => "Vengeance",
+            Spec::Balance => "Balance",
+            Spec::Feral => "Feral",
+            Spec::Guardian => "Guardian",
+            Spec::RestorationDruid | Spec::RestorationShaman => "Restoration",
+            Spec::Augmentation => "Augmentation",
+            Spec::Devastation => "Devastation",
+            Spec::Preservation => "Preservation",
+            Spec::BeastMastery => "Beast Mastery",
+            Spec::Marksmanship => "Marksmanship",
+            Spec::Survival => "Survival",
+            Spec::Arcane => "Arcane",
+            Spec::Fire => "Fire",
+            Spec::Brewmaster => "Brewmaster",
+            Spec::Mistweaver => "Mistweaver",
+            Spec::Windwalker => "Windwalker",
+            Spec::HolyPaladin | Spec::HolyPriest => "Holy",
+            Spec::ProtectionPaladin | Spec::ProtectionWarrior => "Protection",
+            Spec::Retribution => "Retribution",
+            Spec::Discipline => "Discipline",
+            Spec::Shadow => "Shadow",
+            Spec::Assassination => "Assassination",
+            Spec::Outlaw => "Outlaw",
+            Spec::Subtlety => "Subtlety",
+            Spec::Elemental => "Elemental",
+            Spec::Enhancement => "Enhancement",
+            Spec::Affliction => "Affliction",
+            Spec::Demonology => "Demonology",
+            Spec::Destruction => "Destruction",
+            Spec::Arms => "Arms",
+            Spec::Fury => "Fury",
+        }
+    }
+
+    /// "Devastation Evoker"
+    pub fn label(&self) -> String {
+        format!("{} {}", self.name(), self.class().display_name())
+    }
+
+    pub fn icon_url(&self) -> String {
+        let icon = match self {
+            Spec::Blood => "spell_deathknight_bloodpresence",
+            Spec::FrostDeathKnight => "spell_deathknight_frostpresence",
+            Spec::Unholy => "spell_deathknight_unholypresence",
+            Spec::Havoc => "ability_demonhunter_specdps",
+            Spec::Vengeance => "ability_demonhunter_spectank",
+            Spec::Balance => "spell_nature_starfall",
+            Spec::Feral => "ability_druid_catform",
+            Spec::Guardian => "ability_racial_bearform",
+            Spec::RestorationDruid => "spell_nature_healingtouch",
+            Spec::Augmentation => "classicon_evoker_augmentation",
+            Spec::Devastation => "classicon_evoker_devastation",
+            Spec::Preservation => "classicon_evoker_preservation",
+            Spec::BeastMastery => "ability_hunter_bestialdiscipline",
+            Spec::Marksmanship => "ability_hunter_focusedaim",
+            Spec::Survival => "ability_hunter_camouflage",
+            Spec::Arcane => "spell_holy_magicalsentry",
+            Spec::Fire => "spell_fire_firebolt02",
+            Spec::FrostMage => "spell_frost_frostbolt02",
+            Spec::Brewmaster => "spell_monk_brewmaster_spec",
+            Spec::Mistweaver => "spell_monk_mistweaver_spec",
+            Spec::Windwalker => "spell_monk_windwalker_spec",
+            Spec::HolyPaladin => "spell_holy_holybolt",
+            Spec::ProtectionPaladin => "ability_paladin_shieldofthetemplar",
+            Spec::Retribution => "spell_holy_auraoflight",
+            Spec::Discipline => "spell_holy_powerwordshield",
+            Spec::HolyPriest => "spell_holy_guardianspirit",
+            Spec::Shadow => "spell_shadow_shadowwordpain",
+            Spec::Assassination => "ability_rogue_deadlybrew",
+            Spec::Outlaw => "inv_sword_30",
+            Spec::Subtlety => "ability_stealth",
+            Spec::Elemental => "spell_nature_lightning",
+            Spec::Enhancement => "spell_shaman_improvedstormstrike",
+            Spec::RestorationShaman => "spell_nature_magicimmunity",
+            Spec::Affliction => "spell_shadow_deathcoil",
+            Spec::Demonology => "spell_shadow_metamorphosis",
+            Spec::Destruction => "spell_shadow_rainoffire",
+            Spec::Arms => "ability_warrior_savageblow",
+            Spec::Fury => "ability_warrior_innerrage",
+            Spec::ProtectionWarrior => "ability_warrior_defensivestance",
+        };
+        format!("https://wow.zamimg.com/images/wow/icons/large/{}.jpg", icon)
+    }
+
+    /// find a spec from the class and in game spec name, the way wowaudit and raider.io send them.
+    pub fn from_class(class: PlayerClass, name: &str) -> Option<Spec> {
+        let name_key = normalize(name);
+        class.specs().into_iter().find(|spec| normalize(spec.name()) == name_key)
+    }
+}
+
+impl std::fmt::Display for Spec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Parses our variant names ("FrostMage"), "Frost Mage", "Beast Mastery", and any
+// spec name that only one class has ("Devastation"). A bare "Frost" is ambiguous and fails.
+impl std::str::FromStr for Spec {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value_key = normalize(value);
+        if let Some(spec) = Spec::ALL.into_iter().find(|spec| {
+            normalize(&format!("{:?}", spec)) == value_key
+                || normalize(&format!("{}{}", spec.name(), spec.class())) == value_key
+        }) {
+            return Ok(spec);
+        }
+
+        let mut matches = PlayerClass::ALL
+            .into_iter()
+            .filter_map(|class| Spec::from_class(class, value));
+        match (matches.next(), matches.next()) {
+            (Some(spec), None) => Ok(spec),
+            (Some(_), Some(_)) => Err(format!("spec is ambiguous without a class: {}", value)),
+            _ => Err(format!("unknown spec: {}", value)),
+        }
+    }
+}
+
+/// "Devastation Evoker" when the spec is known, otherwise "Evoker".
+pub fn class_label(class: PlayerClass, spec: Option<Spec>) -> String {
+    match spec {
+        Some(spec) => spec.label(),
+        None => class.display_name().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_spec_names_need_a_class() {
+        for name in ["Holy", "Frost", "Protection", "Restoration"] {
+            let error = name.parse::<Spec>().unwrap_err();
+            assert!(error.contains("ambiguous"), "{}: {}", name, error);
+        }
+        assert!("Frostfire".parse::<Spec>().unwrap_err().starts_with("unknown spec"));
+    }
+
+    #[test]
+    fn spec_spellings() {
+        let cases = [
+            ("Frost Mage", Spec::FrostMage),
+            ("FrostMage", Spec::FrostMage),
+            ("FrostDeathKnight", Spec::FrostDeathKnight),
+            ("frost death knight", Spec::FrostDeathKnight),
+            ("Holy Priest", Spec::HolyPriest),
+            ("Devastation", Spec::Devastation),
+            ("Beast Mastery", Spec::BeastMastery),
+            ("beast-mastery", Spec::BeastMastery),
+        ];
+        for (value, spec) in cases {
+            assert_eq!(value.parse::<Spec>(), Ok(spec), "{}", value);
+        }
+    }
+
+    #[test]
+    fn class_spellings() {
+        for value in ["DeathKnight", "Death Knight", "death knight", "death-knight"] {
+            assert_eq!(value.parse::<PlayerClass>(), Ok(PlayerClass::DeathKnight), "{}", value);
+        }
+        assert_eq!("Demon Hunter".parse::<PlayerClass>(), Ok(PlayerClass::DemonHunter));
+        assert!("Necromancer".parse::<PlayerClass>().is_err());
+    }
+
+    #[test]
+    fn every_spec_round_trips() {
+        for spec in Spec::ALL {
+            assert!(spec.class().specs().contains(&spec), "{:?}", spec);
+            assert_eq!(Spec::from_class(spec.class(), spec.name()), Some(spec), "{:?}", spec);
+            assert_eq!(spec.label().parse::<Spec>(), Ok(spec), "{:?}", spec);
+            assert_eq!(format!("{:?}", spec).parse::<Spec>(), Ok(spec), "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn default_roles() {
+        let roles = |class: PlayerClass| -> Vec<PlayerRole> { class.specs().iter().map(Spec::default_role).collect() };
+        for class in [PlayerClass::Hunter, PlayerClass::Mage, PlayerClass::Rogue, PlayerClass::Warlock] {
+            assert_eq!(roles(class), [PlayerRole::Dps; 3], "{}", class);
+        }
+        assert_eq!(roles(PlayerClass::DemonHunter), [PlayerRole::Dps, PlayerRole::Tank]);
+        assert_eq!(roles(PlayerClass::Druid), [PlayerRole::Dps, PlayerRole::Dps, PlayerRole::Tank, PlayerRole::Healer]);
+        assert_eq!(roles(PlayerClass::Priest), [PlayerRole::Healer, PlayerRole::Healer, PlayerRole::Dps]);
+        assert_eq!(roles(PlayerClass::Evoker), [PlayerRole::Dps, PlayerRole::Dps, PlayerRole::Healer]);
+        assert_eq!(roles(PlayerClass::Warrior), [PlayerRole::Dps, PlayerRole::Dps, PlayerRole::Tank]);
+    }
+
+    #[test]
+    fn roles_from_other_sites() {
+        assert_eq!("Heal".parse::<PlayerRole>(), Ok(PlayerRole::Healer));
+        assert_eq!("healing".parse::<PlayerRole>(), Ok(PlayerRole::Healer));
+        assert_eq!("Melee".parse::<PlayerRole>(), Ok(PlayerRole::Dps));
+        assert!("support".parse::<PlayerRole>().is_err());
+    }
+}
@@ -0,0 +1,549 @@
+use serde::{Deserialize, Serialize};
+
+use crate::player_metadata::Player;
+use crate::season::SeasonConfig;
+use crate::realm::normalize_realm;
+use crate::time::Region;
+use crate::wow::{PlayerClass, PlayerRole, Spec};
+
+// +--------------------------+
+// | wowaudit historical_data |
+// +--------------------------+
+// Shape of https://wowaudit.com/v1/historical_data (served from R2 as /events).
+// Only the fields we use are typed, wowaudit can add fields without breaking us.
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistoricalData {
+    /// wowaudit's id for the week this data covers.
+    #[serde(default)]
+    pub period: Option<u32>,
+    pub characters: Vec<HistoricalCharacter>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistoricalCharacter {
+    #[serde(default)]
+    pub id: Option<u64>,
+    pub name: String,
+    pub realm: String,
+    /// null when wowaudit has not synced the character this week.
+    #[serde(default)]
+    pub data: Option<WeeklyData>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WeeklyData {
+    #[serde(default)]
+    pub dungeons_done: Vec<DungeonRun>,
+    #[serde(default)]
+    pub world_quests_done: Option<u32>,
+    #[serde(default)]
+    pub regular_mythic_dungeons_done: Option<u32>,
+    /// item level of each unlocked vault slot, when wowaudit has synced them.
+    #[serde(default)]
+    pub vault_options: Option<VaultOptions>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct VaultOptions {
+    #[serde(default)]
+    pub raids: Option<VaultRow>,
+    #[serde(default)]
+    pub dungeons: Option<VaultRow>,
+    #[serde(default)]
+    pub world: Option<VaultRow>,
+}
+
+/// one row of the vault, null options are locked.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct VaultRow {
+    #[serde(default)]
+    pub option_1: Option<u32>,
+    #[serde(default)]
+    pub option_2: Option<u32>,
+    #[serde(default)]
+    pub option_3: Option<u32>,
+}
+
+impl VaultRow {
+    /// item level per slot, None while locked.
+    pub fn slots(&self) -> [Option<u32>; 3] {
+        [self.option_1, self.option_2, self.option_3]
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DungeonRun {
+    pub level: u32,
+    /// blizzard's keystone dungeon id (ex: 503 is Ara-Kara).
+    pub dungeon: u32,
+}
+
+#[derive(Debug)]
+pub enum EventsError {
+    Unavailable,
+    Malformed(String),
+}
+
+impl std::fmt::Display for EventsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventsError::Unavailable => write!(f, "events.json is unavailable"),
+            EventsError::Malformed(e) => write!(f, "events.json is malformed: {}", e),
+        }
+    }
+}
+
+impl HistoricalData {
+    pub fn from_json(json: &str) -> Result<Self, EventsError> {
+        serde_json::from_str(json).map_err(|e| EventsError::Malformed(e.to_string()))
+    }
+}
+
+// +---------------------+
+// | wowaudit characters |
+// +---------------------+
+// Shape of https://wowaudit.com/v1/characters (stored in R2 as content/characters.json).
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuditCharacter {
+    #[serde(default)]
+    pub id: Option<u64>,
+    pub name: String,
+    pub realm: String,
+    /// ex: "Death Knight"
+    pub class: String,
+    /// "Tank", "Heal", "Melee" or "Ranged"
+    #[serde(default)]
+    pub role: Option<String>,
+    /// main spec when wowaudit has one, ex: "Devastation"
+    #[serde(default)]
+    pub spec: Option<String>,
+    /// guild rank, ex: "Raider"
+    #[serde(default)]
+    pub rank: Option<String>,
+}
+
+/// parse a characters response, an empty list is treated as malformed (bad token or sync).
+pub fn parse_characters(json: &str) -> Result<Vec<AuditCharacter>, String> {
+    let characters: Vec<AuditCharacter> =
+        serde_json::from_str(json).map_err(|e| format!("characters.json is malformed: {}", e))?;
+    if characters.is_empty() {
+        return Err("characters.json has no characters".to_string());
+    }
+    Ok(characters)
+}
+
+/// guild ranks that make a raid roster.
+pub const RAID_RANKS: [&str; 2] = ["Officer", "Raider"];
+
+/// a character whose class string isn't one of ours, reported instead of guessed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmappedCharacter {
+    pub name: String,
+    pub realm: String,
+    pub class: String,
+}
+
+impl std::fmt::Display for UnmappedCharacter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{} has unknown class \"{}\"", self.name, self.realm, self.class)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RosterImport {
+    pub players: Vec<Player>,
+    pub unmapped: Vec<UnmappedCharacter>,
+}
+
+/// Map a characters response into roster players, keeping only the given ranks (case insensitive).
+/// Role comes from the spec when wowaudit knows it, then its role column, then Dps.
+pub fn import_characters(json: &str, ranks: &[&str]) -> Result<RosterImport, String> {
+    let mut import = RosterImport::default();
+    for character in parse_characters(json)? {
+        let rank = character.rank.as_deref().unwrap_or_default();
+        if !ranks.iter().any(|wanted| wanted.eq_ignore_ascii_case(rank.trim())) {
+            continue;
+        }
+        match to_player(&character) {
+            Some(player) => import.players.push(player),
+            None => import.unmapped.push(UnmappedCharacter {
+                name: character.name,
+                realm: normalize_realm(&character.realm),
+                class: character.class,
+            }),
+        }
+    }
+    Ok(import)
+}
+
+fn to_player(character: &AuditCharacter) -> Option<Player> {
+    let class = character.class.parse::<PlayerClass>().ok()?;
+    let spec = character.spec.as_deref().and_then(|spec| Spec::from_class(class, spec));
+    let role = match spec {
+        Some(spec) => spec.default_role(),
+        None => character
+            .role
+            .as_deref()
+            .and_then(|role| role.parse::<PlayerRole>().ok())
+            .unwrap_or(PlayerRole::Dps),
+    };
+    Some(Player {
+        name: character.name.trim().to_string(),
+        class,
+        realm: normalize_realm(&character.realm),
+        // the wowaudit team is on us realms.
+        region: Region::Us,
+        member: None,
+        role,
+        spec,
+    })
+}
+
+// +----------------+
+// | Key Level Tier |
+// +----------------+
+
+/// item quality colour for a key level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyTier {
+    Common,    // +0 - +8, the old marquee coloured every key up to +8 green.
+    Rare,      // +9 - +11
+    Epic,      // +12 - +14
+    Legendary, // +15
+    Artifact,  // +16 - +30
+    Poor,      // above +30, we shouldn't see these.
+}
+
+impl KeyTier {
+    pub fn from_level(level: u32) -> Self {
+        match level {
+            0..=8 => KeyTier::Common,
+            9..=11 => KeyTier::Rare,
+            12..=14 => KeyTier::Epic,
+            15 => KeyTier::Legendary,
+            16..=30 => KeyTier::Artifact,
+            _ => KeyTier::Poor,
+        }
+    }
+
+    pub fn color(&self) -> &'static str {
+        match self {
+            KeyTier::Common => "#1eff00",
+            KeyTier::Rare => "#0070dd",
+            KeyTier::Epic => "#a335ee",
+            KeyTier::Legendary => "#ff8000",
+            KeyTier::Artifact => "#e6cc80",
+            KeyTier::Poor => "#9d9d9d",
+        }
+    }
+}
+
+// +-------------+
+// | Great Vault |
+// +-------------+
+
+/// runs needed for each M+ vault slot.
+pub const VAULT_DUNGEON_THRESHOLDS: [usize; 3] = [1, 4, 8];
+
+/// One character's vault for the week wowaudit's data covers.
+#[derive(Debug, Clone)]
+pub struct VaultProgress {
+    pub name: String,
+    pub realm: String,
+    /// keystones plus mythic 0s, None when wowaudit hasn't synced the character this week.
+    pub dungeons_done: Option<usize>,
+    /// key level each M+ slot unlocks, 0 is a mythic 0. None while locked.
+    pub dungeon_slots: [Option<u32>; 3],
+    /// raid slot item levels, None when wowaudit has no vault data.
+    pub raid_slots: Option<[Option<u32>; 3]>,
+}
+
+impl VaultProgress {
+    pub fn from_character(character: &HistoricalCharacter) -> Self {
+        let data = character.data.as_ref();
+        let mut levels: Vec<u32> = data
+            .map(|data| data.dungeons_done.iter().map(|run| run.level).collect())
+            .unwrap_or_default();
+        levels.sort_by_key(|level| std::cmp::Reverse(*level));
+        // mythic 0s fill the vault after every keystone.
+        let mythic_zeros = data.and_then(|data| data.regular_mythic_dungeons_done).unwrap_or_default();
+        levels.extend(std::iter::repeat_n(0, mythic_zeros as usize));
+
+        VaultProgress {
+            name: character.name.clone(),
+            realm: character.realm.clone(),
+            dungeons_done: data.map(|_| levels.len()),
+            dungeon_slots: VAULT_DUNGEON_THRESHOLDS.map(|runs| levels.get(runs - 1).copied()),
+            raid_slots: data
+                .and_then(|data| data.vault_options.as_ref())
+                .and_then(|options| options.raids.as_ref())
+                .map(VaultRow::slots),
+        }
+    }
+
+    pub fn dungeon_slots_unlocked(&self) -> usize {
+        self.dungeon_slots.iter().flatten().count()
+    }
+
+    /// fewer dungeons than `minimum` this week (or no data at all).
+    pub fn is_below(&self, minimum: usize) -> bool {
+        self.dungeons_done.unwrap_or_default() < minimum
+    }
+}
+
+// +------------------+
+// | Scrolling Events |
+// +------------------+
+
+/// one line of the home page's Recent Mythic+ marquee.
+#[derive(Debug, Clone)]
+pub enum MarqueeLine {
+    Run {
+        character: String,
+        key_level: u32,
+        dungeon: String,
+        icon_url: Option<String>,
+        tier: KeyTier,
+    },
+    NoData {
+        character: String,
+        realm: String,
+    },
+}
+
+impl HistoricalData {
+    /// every key done this week, one line per run. dungeons are named from the current season's pool.
+    pub fn marquee_lines(&self, seasons: &SeasonConfig) -> Vec<MarqueeLine> {
+        let mut lines = Vec::new();
+        for character in &self.characters {
+            let Some(data) = &character.data else {
+                lines.push(MarqueeLine::NoData {
+                    character: character.name.clone(),
+                    realm: character.realm.clone(),
+                });
+                continue;
+            };
+            for run in &data.dungeons_done {
+                let dungeon = seasons.dungeon(run.dungeon);
+                lines.push(MarqueeLine::Run {
+                    character: character.name.clone(),
+                    key_level: run.level,
+                    dungeon: match dungeon {
+                        Some(dungeon) => dungeon.name.clone(),
+                        None => format!("Unknown ({})", run.dungeon),
+                    },
+                    icon_url: dungeon.map(|dungeon| dungeon.icon_url.clone()),
+                    tier: KeyTier::from_level(run.level),
+                });
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // synthetic characters shaped like a https://wowaudit.com/v1/characters response.
+    const CHARACTERS: &str = include_str!("../fixtures/wowaudit-characters.json");
+
+    fn player<'a>(import: &'a RosterImport, name: &str) -> &'a Player {
+        import
+            .players
+            .iter()
+            .find(|player| player.name == name)
+            .unwrap_or_else(|| panic!("{} was not imported", name))
+    }
+
+    #[test]
+    fn keeps_only_raid_ranks() {
+        let import = import_characters(CHARACTERS, &RAID_RANKS).unwrap();
+        let names: Vec<&str> = import.players.iter().map(|player| player.name.as_str()).collect();
+
+        assert_eq!(
+            names,
+            ["Whare", "Piptide", "Notshodo", "Nuzzler", "Jaemsy", "Philfishh", "Indico", "Kolzane", "Ppddk", "Fliptwisty"]
+        );
+        // trials, alts, socials and characters without a rank are left out.
+        for name in ["Purpleheal", "Nuzzsin", "Sylvána", "Ovtlaw"] {
+            assert!(!names.contains(&name), "{} should not be imported", name);
+        }
+
+        let trials = import_characters(CHARACTERS, &["trial"]).unwrap();
+        assert_eq!(trials.players.len(), 1);
+        assert_eq!(trials.players[0].name, "Purpleheal");
+    }
+
+    #[test]
+    fn role_comes_from_the_spec_first() {
+        let import = import_characters(CHARACTERS, &RAID_RANKS).unwrap();
+
+        // wowaudit says Ranged, but Preservation heals.
+        let notshodo = player(&import, "Notshodo");
+        assert_eq!(notshodo.spec, Some(Spec::Preservation));
+        assert_eq!(notshodo.role, PlayerRole::Healer);
+        // no spec, the role column decides.
+        let nuzzler = player(&import, "Nuzzler");
+        assert_eq!(nuzzler.spec, None);
+        assert_eq!(nuzzler.role, PlayerRole::Dps);
+        let piptide = player(&import, "Piptide");
+        assert_eq!(piptide.spec, Some(Spec::RestorationShaman));
+        assert_eq!(piptide.role, PlayerRole::Healer);
+        // "Holy" isn't a Warrior spec, so it is dropped and the role column is used.
+        let jaemsy = player(&import, "Jaemsy");
+        assert_eq!(jaemsy.spec, None);
+        assert_eq!(jaemsy.role, PlayerRole::Tank);
+    }
+
+    #[test]
+    fn realms_are_normalized() {
+        let import = import_characters(CHARACTERS, &RAID_RANKS).unwrap();
+
+        assert_eq!(player(&import, "Philfishh").realm, "Area 52");
+        assert_eq!(player(&import, "Indico").realm, "Zul'jin");
+        assert_eq!(player(&import, "Whare").realm, "Stormrage");
+        assert!(import.players.iter().all(|player| player.region == Region::Us));
+    }
+
+    #[test]
+    fn unknown_classes_are_reported() {
+        let import = import_characters(CHARACTERS, &RAID_RANKS).unwrap();
+
+        assert_eq!(
+            import.unmapped,
+            [
+                UnmappedCharacter {
+                    name: "Wardenvex".to_string(),
+                    realm: "Stormrage".to_string(),
+                    class: "Warden".to_string(),
+                },
+                UnmappedCharacter {
+                    name: "Rangedude".to_string(),
+                    realm: "Area 52".to_string(),
+                    class: "Dark Ranger".to_string(),
+                },
+            ]
+        );
+        assert!(import.players.iter().all(|player| player.name != "Wardenvex" && player.name != "Rangedude"));
+        // multi-word classes still map.
+        assert_eq!(player(&import, "Ppddk").class, PlayerClass::DeathKnight);
+        assert_eq!(player(&import, "Fliptwisty").class, PlayerClass::DemonHunter);
+    }
+
+    #[test]
+    fn empty_or_malformed_responses_are_errors() {
+        assert!(import_characters("[]", &RAID_RANKS).is_err());
+        assert!(import_characters("{\"error\": \"unauthorized\"}", &RAID_RANKS).is_err());
+    }
+
+    // synthetic, shaped like https://wowaudit.com/v1/historical_data: one character per vault case.
+    const HISTORICAL_DATA: &str = include_str!("../fixtures/wowaudit-historical-data.json");
+
+    fn vault(name: &str) -> VaultProgress {
+        let data = HistoricalData::from_json(HISTORICAL_DATA).unwrap();
+        let character = data
+            .characters
+            .iter()
+            .find(|character| character.name == name)
+            .unwrap_or_else(|| panic!("{} is not in the fixture", name));
+        VaultProgress::from_character(character)
+    }
+
+    #[test]
+    fn no_runs_unlock_nothing() {
+        let vault = vault("Zerorun");
+
+        assert_eq!(vault.dungeons_done, Some(0));
+        assert_eq!(vault.dungeon_slots, [None, None, None]);
+        assert_eq!(vault.dungeon_slots_unlocked(), 0);
+        // the row is there, every slot still locked.
+        assert_eq!(vault.raid_slots, Some([None, None, None]));
+    }
+
+    #[test]
+    fn one_run_unlocks_the_first_slot() {
+        let vault = vault("Onerun");
+
+        assert_eq!(vault.dungeons_done, Some(1));
+        assert_eq!(vault.dungeon_slots, [Some(10), None, None]);
+        assert_eq!(vault.raid_slots, Some([Some(684), None, None]));
+    }
+
+    #[test]
+    fn four_runs_unlock_two_slots_highest_first() {
+        let vault = vault("Fourkeys");
+
+        assert_eq!(vault.dungeons_done, Some(4));
+        // 12, 10, 9, 7: the best key, then the 4th best.
+        assert_eq!(vault.dungeon_slots, [Some(12), Some(7), None]);
+        assert_eq!(vault.dungeon_slots_unlocked(), 2);
+        // no vault_options synced.
+        assert_eq!(vault.raid_slots, None);
+    }
+
+    #[test]
+    fn mythic_zeros_fill_after_keystones() {
+        // +9 then three M0s.
+        assert_eq!(vault("Keysandzeros").dungeon_slots, [Some(9), Some(0), None]);
+
+        // 15, 13, 12, 11, 10, M0, M0: one short of the third slot.
+        let vault = vault("Sevenmixed");
+        assert_eq!(vault.dungeons_done, Some(7));
+        assert_eq!(vault.dungeon_slots, [Some(15), Some(11), None]);
+    }
+
+    #[test]
+    fn eight_runs_unlock_every_slot() {
+        assert_eq!(vault("Mythiczero").dungeon_slots, [Some(0), Some(0), Some(0)]);
+
+        let vault = vault("Eightplus");
+        assert_eq!(vault.dungeons_done, Some(10));
+        // 18 down to 10, then an M0.
+        assert_eq!(vault.dungeon_slots, [Some(18), Some(15), Some(11)]);
+        assert_eq!(vault.raid_slots, Some([Some(697), Some(684), Some(671)]));
+    }
+
+    #[test]
+    fn unsynced_character_has_no_vault() {
+        let vault = vault("Unsynced");
+
+        assert_eq!(vault.dungeons_done, None);
+        assert_eq!(vault.dungeon_slots, [None, None, None]);
+        assert_eq!(vault.raid_slots, None);
+        assert!(vault.is_below(1));
+    }
+
+    #[test]
+    fn below_minimum_is_strictly_fewer_runs() {
+        assert!(!vault("Fourkeys").is_below(4));
+        assert!(vault("Fourkeys").is_below(5));
+        assert!(vault("Onerun").is_below(4));
+        assert!(!vault("Zerorun").is_below(0));
+    }
+
+    #[test]
+    fn key_tiers() {
+        let tiers: Vec<KeyTier> = [0, 1, 2, 8, 9, 11, 12, 14, 15, 16, 30, 31].into_iter().map(KeyTier::from_level).collect();
+
+        assert_eq!(
+            tiers,
+            [
+                KeyTier::Common,
+                KeyTier::Common,
+                KeyTier::Common,
+                KeyTier::Common,
+                KeyTier::Rare,
+                KeyTier::Rare,
+                KeyTier::Epic,
+                KeyTier::Epic,
+                KeyTier::Legendary,
+                KeyTier::Artifact,
+                KeyTier::Artifact,
+                KeyTier::Poor,
+            ]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use seemsgood_domain::wowaudit::RAID_RANKS;
+
+mod r2;
+mod release;
+mod roster;
+mod wowaudit;
+
+// +-------+
+// | sgctl |
+// +-------+
+// Replaces the old json_helpers.sh. Every document is parsed with seemsgood_domain before it is
+// written or uploaded, so sgctl can't produce something the worker won't read.
+
+#[derive(Parser)]
+#[command(name = "sgctl", about = "Seems Good guild data tooling")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// rosters.json
+    Roster {
+        #[command(subcommand)]
+        command: RosterCommand,
+    },
+    /// wowaudit historical data (events.json)
+    Events {
+        #[command(subcommand)]
+        command: EventsCommand,
+    },
+    /// documents in the R2_CONTENT bucket
+    Content {
+        #[command(subcommand)]
+        command: ContentCommand,
+    },
+    /// version bumps
+    Release {
+        #[command(subcommand)]
+        command: ReleaseCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum RosterCommand {
+    /// Add (or replace) a roster from wowaudit's character list.
+    Import {
+        /// roster id, the raid kill's fight_key (ex: "Dimensius").
+        #[arg(long)]
+        roster: String,
+        /// guild ranks to include.
+        #[arg(long, value_delimiter = ',', default_values_t = RAID_RANKS.map(String::from))]
+        ranks: Vec<String>,
+        /// rosters.json to add the roster to, written back in place.
+        #[arg(long, default_value = "templates/assets/rosters.json")]
+        file: PathBuf,
+        #[arg(long, env = "WOWAUDIT_TOKEN", hide_env_values = true)]
+        token: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum EventsCommand {
+    /// Download this week's historical data.
+    Fetch {
+        #[arg(long, default_value = "events.json")]
+        out: PathBuf,
+        #[arg(long, env = "WOWAUDIT_TOKEN", hide_env_values = true)]
+        token: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContentCommand {
+    /// Validate a document and upload it through R2's S3 api.
+    Push {
+        file: PathBuf,
+        /// object key, defaults to content/<file name>.
+        #[arg(long)]
+        key: Option<String>,
+        /// seasons.json used to check raids.json.
+        #[arg(long, default_value = "templates/assets/seasons.json")]
+        seasons: PathBuf,
+        /// members.json used to check rosters.json.
+        #[arg(long, default_value = "templates/assets/members.json")]
+        members: PathBuf,
+        /// rosters.json every raids.json kill needs a roster in.
+        #[arg(long, default_value = "templates/assets/rosters.json")]
+        rosters: PathBuf,
+        /// raids.json whose kills a pushed rosters.json must still cover.
+        #[arg(long, default_value = "templates/assets/raids.json")]
+        raids: PathBuf,
+        #[arg(long, env = "R2_BUCKET", default_value = "sg-cfr2")]
+        bucket: String,
+        #[arg(long, env = "R2_ACCOUNT_ID")]
+        account_id: String,
+        #[arg(long, env = "R2_ACCESS_KEY_ID")]
+        access_key_id: String,
+        #[arg(long, env = "R2_SECRET_ACCESS_KEY", hide_env_values = true)]
+        secret_access_key: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReleaseCommand {
+    /// Set Cargo.toml's version, from the vX.Y.Z branch name unless one is given.
+    Bump {
+        version: Option<String>,
+        #[arg(long, default_value = "Cargo.toml")]
+        manifest: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Roster {
+            command: RosterCommand::Import { roster, ranks, file, token },
+        } => roster::import(&token, &roster, &ranks, &file),
+        Command::Events {
+            command: EventsCommand::Fetch { out, token },
+        } => {
+            let json = wowaudit::fetch_events(&token)?;
+            write_file(&out, &json)?;
+            println!("wrote {}", out.display());
+            Ok(())
+        }
+        Command::Content {
+            command:
+                ContentCommand::Push {
+                    file,
+                    key,
+                    seasons,
+                    members,
+                    rosters,
+                    raids,
+                    bucket,
+                    account_id,
+                    access_key_id,
+                    secret_access_key,
+                },
+        } => {
+            let credentials = r2::Credentials {
+                account_id,
+                access_key_id,
+                secret_access_key,
+            };
+            let references = r2::References {
+                seasons: &seasons,
+                members: &members,
+                rosters: &rosters,
+                raids: &raids,
+            };
+            r2::push(&credentials, &bucket, &file, key, &references)
+        }
+        Command::Release {
+            command: ReleaseCommand::Bump { version, manifest },
+        } => release::bump(version, &manifest),
+    }
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+pub(crate) fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,255 @@
+use std::path::Path;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use seemsgood_domain::member::MemberDocument;
+use seemsgood_domain::player_metadata::{RaidManifest, RosterDocument};
+use seemsgood_domain::season::SeasonConfig;
+use seemsgood_domain::wowaudit::{parse_characters, HistoricalData};
+use sha2::{Digest, Sha256};
+
+use crate::read_file;
+
+// +--------------+
+// | content push |
+// +--------------+
+// Uploads through R2's S3 compatible api, signed with AWS SigV4.
+// Credentials are an R2 api token (dashboard: R2 > Manage API tokens).
+
+/// R2 takes any sigv4 region, "auto" is the documented one.
+const R2_REGION: &str = "auto";
+
+pub struct Credentials {
+    pub account_id: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+/// the local copies a pushed document is checked against.
+pub struct References<'a> {
+    pub seasons: &'a Path,
+    pub members: &'a Path,
+    pub rosters: &'a Path,
+    pub raids: &'a Path,
+}
+
+/// Validate a document (when it's one the worker parses) and upload it.
+pub fn push(
+    credentials: &Credentials,
+    bucket: &str,
+    file: &Path,
+    key: Option<String>,
+    references: &References,
+) -> Result<(), String> {
+    let body = read_file(file)?;
+    let file_name = file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("{} has no file name", file.display()))?;
+    let key = key.unwrap_or_else(|| format!("content/{}", file_name));
+    let document = key.rsplit('/').next().unwrap_or(&key);
+
+    validate(document, &body, references)?;
+    put_object(credentials, bucket, &key, &body, content_type(document))?;
+    println!("uploaded {} to {}/{}", file.display(), bucket, key);
+    Ok(())
+}
+
+fn validate(document: &str, body: &str, references: &References) -> Result<(), String> {
+    let seasons = || -> Result<SeasonConfig, String> {
+        SeasonConfig::from_json(&read_file(references.seasons)?).map_err(|e| e.to_string())
+    };
+    let checked = match document {
+        "rosters.json" => {
+            let members = MemberDocument::from_json(&read_file(references.members)?).map_err(|e| e.to_string())?;
+            let raids = RaidManifest::from_json(&read_file(references.raids)?, &seasons()?).map_err(|e| e.to_string())?;
+            RosterDocument::from_json(body)
+                .and_then(|rosters| members.check_roster(&rosters).and_then(|_| raids.check_rosters(&rosters)))
+                .map_err(|e| e.to_string())
+        }
+        "members.json" => MemberDocument::from_json(body).map(|_| ()).map_err(|e| e.to_string()),
+        "seasons.json" => SeasonConfig::from_json(body).map(|_| ()).map_err(|e| e.to_string()),
+        "raids.json" => {
+            let rosters = RosterDocument::from_json(&read_file(references.rosters)?).map_err(|e| e.to_string())?;
+            RaidManifest::from_json(body, &seasons()?)
+                .and_then(|raids| raids.check_rosters(&rosters))
+                .map_err(|e| e.to_string())
+        }
+        "events.json" => HistoricalData::from_json(body).map(|_| ()).map_err(|e| e.to_string()),
+        "characters.json" => parse_characters(body).map(|_| ()),
+        _ => {
+            println!("{} has no schema, uploading as-is", document);
+            Ok(())
+        }
+    };
+    checked.map_err(|e| format!("not uploading {}: {}", document, e))
+}
+
+fn content_type(document: &str) -> &'static str {
+    match document.rsplit('.').next() {
+        Some("json") => "application/json",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        Some("png") => "image/png",
+        _ => "application/octet-stream",
+    }
+}
+
+fn put_object(
+    credentials: &Credentials,
+    bucket: &str,
+    key: &str,
+    body: &str,
+    content_type: &str,
+) -> Result<(), String> {
+    let host = format!("{}.r2.cloudflarestorage.com", credentials.account_id);
+    let path = format!("/{}/{}", uri_encode(bucket), uri_encode(key));
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+    let payload_hash = hex::encode(Sha256::digest(body.as_bytes()));
+
+    let signed_headers = "content-type;host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{}\n\ncontent-type:{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        path, content_type, host, payload_hash, amz_date, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, R2_REGION);
+    let signature = sign(&credentials.secret_access_key, &amz_date, R2_REGION, &canonical_request);
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    );
+
+    ureq::put(&format!("https://{}{}", host, path))
+        .set("content-type", content_type)
+        .set("x-amz-content-sha256", &payload_hash)
+        .set("x-amz-date", &amz_date)
+        .set("authorization", &authorization)
+        .send_string(body)
+        .map(|_| ())
+        .map_err(|e| format!("R2 upload of {} failed: {}", key, e))
+}
+
+// sigv4 signature of an s3 request, `amz_date` is YYYYMMDDTHHMMSSZ.
+fn sign(secret_access_key: &str, amz_date: &str, region: &str, canonical_request: &str) -> String {
+    let date = &amz_date[..8];
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}/{}/s3/aws4_request\n{}",
+        amz_date,
+        date,
+        region,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let signing_key = signing_key(secret_access_key, date, region, "s3");
+    hex::encode(hmac(&signing_key, string_to_sign.as_bytes()))
+}
+
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    [region, service, "aws4_request"].iter().fold(
+        hmac(format!("AWS4{}", secret_access_key).as_bytes(), date.as_bytes()),
+        |key, part| hmac(&key, part.as_bytes()),
+    )
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// sigv4 encoding: everything but unreserved characters and '/' is percent encoded.
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // AWS's example keys from the sigv4 docs.
+    const SECRET: &str = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
+
+    fn asset(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../templates/assets").join(name)
+    }
+
+    fn check(document: &str, body: &str) -> Result<(), String> {
+        let (seasons, members, rosters, raids) =
+            (asset("seasons.json"), asset("members.json"), asset("rosters.json"), asset("raids.json"));
+        let references = References {
+            seasons: &seasons,
+            members: &members,
+            rosters: &rosters,
+            raids: &raids,
+        };
+        validate(document, body, &references)
+    }
+
+    #[test]
+    fn signing_key_matches_aws() {
+        // https://docs.aws.amazon.com/IAM/latest/UserGuide/signing-elements.html
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+
+        assert_eq!(hex::encode(key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+
+    #[test]
+    fn signature_matches_aws_get_object_example() {
+        // https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html, "GET Object".
+        let canonical_request = "GET\n/test.txt\n\nhost:examplebucket.s3.amazonaws.com\nrange:bytes=0-9\n\
+            x-amz-content-sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\n\
+            x-amz-date:20130524T000000Z\n\nhost;range;x-amz-content-sha256;x-amz-date\n\
+            e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+        assert_eq!(
+            sign(SECRET, "20130524T000000Z", "us-east-1", canonical_request),
+            "f0e8bdb87c964420e857bd35b5d6ed310bd44f0170aba48dd91039c6036bdb41"
+        );
+    }
+
+    #[test]
+    fn keys_are_uri_encoded() {
+        assert_eq!(uri_encode("content/raider-expectations.md"), "content/raider-expectations.md");
+        assert_eq!(uri_encode("scores/tww s3+1.json"), "scores/tww%20s3%2B1.json");
+        assert_eq!(uri_encode("Sylvána~"), "Sylv%C3%A1na~");
+    }
+
+    #[test]
+    fn bundled_documents_pass() {
+        for document in ["seasons.json", "members.json", "rosters.json", "raids.json"] {
+            let body = read_file(&asset(document)).unwrap();
+            check(document, &body).unwrap_or_else(|e| panic!("{}", e));
+        }
+    }
+
+    #[test]
+    fn kill_without_a_roster_is_not_uploaded() {
+        let body = read_file(&asset("raids.json")).unwrap().replace("\"fight_key\": \"Gallywix\"", "\"fight_key\": \"Ragnaros\"");
+        let error = check("raids.json", &body).unwrap_err();
+
+        assert!(error.starts_with("not uploading raids.json"), "{}", error);
+        assert!(error.contains("fight_key \"Ragnaros\" has no roster"), "{}", error);
+    }
+
+    #[test]
+    fn rosters_must_keep_every_kills_roster() {
+        let mut rosters: serde_json::Value = serde_json::from_str(&read_file(&asset("rosters.json")).unwrap()).unwrap();
+        rosters["rosters"].as_object_mut().unwrap().remove("Gallywix");
+        let error = check("rosters.json", &rosters.to_string()).unwrap_err();
+
+        assert!(error.contains("fight_key \"Gallywix\" has no roster"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_documents_are_not_checked() {
+        assert!(check("raider-expectations.md", "# Expectations").is_ok());
+        assert!(check("events.json", "not json").is_err());
+    }
+}
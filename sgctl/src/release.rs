@@ -0,0 +1,135 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::{read_file, write_file};
+
+// +--------------+
+// | release bump |
+// +--------------+
+// Same flow as before: push a vX.Y.Z branch, run this on it, open the PR into main.
+
+pub fn bump(version: Option<String>, manifest: &Path) -> Result<(), String> {
+    let version = match version {
+        Some(version) => version.trim_start_matches('v').to_string(),
+        None => version_from_branch()?,
+    };
+    if !is_semver(&version) {
+        return Err(format!("version must look like X.Y.Z (got: {})", version));
+    }
+
+    let contents = read_file(manifest)?;
+    let (updated, previous) = set_package_version(&contents, &version)
+        .ok_or_else(|| format!("No [package] version in {}", manifest.display()))?;
+    if previous == version {
+        println!("{} already at version {}", manifest.display(), version);
+        return Ok(());
+    }
+    write_file(manifest, &updated)?;
+    println!("Updating {} version: {} -> {}", manifest.display(), previous, version);
+    Ok(())
+}
+
+fn version_from_branch() -> Result<String, String> {
+    let output = Command::new("git")
+        .args(["branch", "--show-current"])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    branch
+        .strip_prefix('v')
+        .filter(|version| is_semver(version))
+        .map(str::to_string)
+        .ok_or_else(|| format!("Branch name must be vX.Y.Z (got: {})", branch))
+}
+
+fn is_semver(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+// rewrite `version = "..."` inside [package] only, dependency versions are left alone.
+fn set_package_version(contents: &str, version: &str) -> Option<(String, String)> {
+    let mut section = "";
+    let mut previous = None;
+    let mut lines = Vec::new();
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            section = trimmed;
+        }
+        if section == "[package]" && previous.is_none() {
+            // `version.workspace = true` has no version of its own to bump.
+            if let Some(("version", value)) = trimmed.split_once('=').map(|(key, value)| (key.trim(), value)) {
+                previous = Some(value.trim().trim_matches('"').to_string());
+                lines.push(format!("version = \"{}\"", version));
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+    let mut updated = lines.join("\n");
+    if contents.ends_with('\n') {
+        updated.push('\n');
+    }
+    previous.map(|previous| (updated, previous))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[workspace]
+members = ["domain", "sgctl"]
+
+[workspace.package]
+version = "0.9.0"
+
+[package]
+name = "prod-seemsgood-web"
+version = "1.1.0"
+edition = "2021"
+
+[dependencies]
+worker = { version = "0.7" }
+serde = "1.0"
+"#;
+
+    #[test]
+    fn semver() {
+        assert!(is_semver("1.2.3"));
+        assert!(is_semver("10.0.12"));
+        for version in ["1.2", "1.2.3.4", "1..3", "v1.2.3", "1.2.3-rc1", ""] {
+            assert!(!is_semver(version), "{} is not X.Y.Z", version);
+        }
+    }
+
+    #[test]
+    fn only_the_package_version_changes() {
+        let (updated, previous) = set_package_version(MANIFEST, "1.2.0").unwrap();
+
+        assert_eq!(previous, "1.1.0");
+        assert_eq!(updated, MANIFEST.replace("version = \"1.1.0\"", "version = \"1.2.0\""));
+        assert!(updated.contains("[workspace.package]\nversion = \"0.9.0\""));
+        assert!(updated.contains("worker = { version = \"0.7\" }"));
+        assert!(updated.ends_with("serde = \"1.0\"\n"));
+    }
+
+    #[test]
+    fn trailing_newline_is_kept_or_left_out() {
+        let (updated, _) = set_package_version("[package]\nversion = \"1.0.0\"\n", "1.0.1").unwrap();
+        assert_eq!(updated, "[package]\nversion = \"1.0.1\"\n");
+
+        let (updated, _) = set_package_version("[package]\nversion = \"1.0.0\"", "1.0.1").unwrap();
+        assert_eq!(updated, "[package]\nversion = \"1.0.1\"");
+    }
+
+    #[test]
+    fn no_package_version() {
+        assert!(set_package_version("[workspace.package]\nversion = \"1.0.0\"\n", "1.0.1").is_none());
+        assert!(set_package_version("[package]\nname = \"sgctl\"\n\n[dependencies]\nclap = { version = \"4\" }\n", "1.0.1").is_none());
+        assert!(set_package_version("[package]\nversion.workspace = true\n", "1.0.1").is_none());
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use seemsgood_domain::player_metadata::{RosterDocument, ROSTERS_SCHEMA_VERSION};
+use seemsgood_domain::wowaudit::import_characters;
+
+use crate::{read_file, wowaudit, write_file};
+
+// +---------------+
+// | roster import |
+// +---------------+
+
+/// Replace one roster in rosters.json with wowaudit's characters of the given ranks.
+pub fn import(token: &str, roster: &str, ranks: &[String], file: &Path) -> Result<(), String> {
+    let mut doc = match file.exists() {
+        true => RosterDocument::from_json(&read_file(file)?).map_err(|e| e.to_string())?,
+        false => RosterDocument {
+            version: ROSTERS_SCHEMA_VERSION,
+            rosters: Default::default(),
+        },
+    };
+
+    let ranks: Vec<&str> = ranks.iter().map(String::as_str).collect();
+    let import = import_characters(&wowaudit::fetch_characters(token)?, &ranks)?;
+    for unmapped in &import.unmapped {
+        eprintln!("skipping {}", unmapped);
+    }
+    let players = import.players;
+    println!("{} players for roster {}", players.len(), roster);
+    doc.rosters.insert(roster.to_string(), players);
+
+    // parse what we are about to write, the same way the worker will.
+    let json = serde_json::to_string_pretty(&doc).map_err(|e| format!("Failed to serialize rosters.json: {}", e))?;
+    RosterDocument::from_json(&json).map_err(|e| e.to_string())?;
+    write_file(file, &format!("{}\n", json))?;
+    println!("wrote {}", file.display());
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use seemsgood_domain::wowaudit::HistoricalData;
+
+// +--------------+
+// | wowaudit api |
+// +--------------+
+
+const WOWAUDIT_API: &str = "https://wowaudit.com/v1";
+
+fn get(path: &str, token: &str) -> Result<String, String> {
+    ureq::get(&format!("{}{}", WOWAUDIT_API, path))
+        .set("accept", "application/json")
+        .set("authorization", token)
+        .call()
+        .map_err(|e| format!("wowaudit {} failed: {}", path, e))?
+        .into_string()
+        .map_err(|e| format!("Failed to read wowaudit {}: {}", path, e))
+}
+
+/// historical_data as returned by wowaudit, checked against the worker's types.
+pub fn fetch_events(token: &str) -> Result<String, String> {
+    let json = get("/historical_data", token)?;
+    let data = HistoricalData::from_json(&json).map_err(|e| e.to_string())?;
+    if data.characters.is_empty() {
+        return Err("events.json has no characters".to_string());
+    }
+    Ok(json)
+}
+
+/// the raw characters response, parsed by the roster importer.
+pub fn fetch_characters(token: &str) -> Result<String, String> {
+    get("/characters", token)
+}
@@ -1,62 +1,38 @@
-// Contact Info for about page.
+use crate::member::Rank;
+use crate::player_metadata::{Member, MemberDocument};
+
+// Contact Info for about page, the guild master(s) and officers in members.json.
 #[derive(Debug)]    
 pub struct ContactInfo {
     pub is_gm: bool,
-    pub name: &'static str,   
-    pub discord: &'static str,   
-    pub battlenet: &'static str,   
+    pub name: String,   
+    pub discord: String,   
+    pub battlenet: Option<String>,   
     pub discord_icon: &'static str,
     pub battlenet_icon: &'static str,
 }
 
-pub fn build_contacts() -> Vec<ContactInfo> {
+pub fn build_contacts(members: &MemberDocument) -> Vec<ContactInfo> {
     let discord_icon = "nf nf-fa-discord";
     let battlenet_icon = "nf nf-fa-battle_net";
-        
-    let contact_info = vec![
-        ContactInfo {
-            is_gm: true,
-            name: "Dub",
-            discord: ".whitechoc",
-            battlenet: "WhiteChoc#1172",
-            discord_icon: discord_icon,
-            battlenet_icon:  battlenet_icon       
-        },
-        ContactInfo {
-            is_gm: true,
-            name: "Ladora",
-            discord: "ladora",
-            battlenet: "Ladora#1644",
-            discord_icon: discord_icon,
-            battlenet_icon:  battlenet_icon       
-        },
-
-        ContactInfo {
-            is_gm: false,
-            name: "Delusionol",
-            discord: "delusionol",
-            battlenet: "Delusionol#1509",
-            discord_icon: discord_icon,
-            battlenet_icon:  battlenet_icon       
-        },
 
-        ContactInfo {
-            is_gm: false,
-            name: "Kolzane",
-            discord: "oogamama",
-            battlenet: "Oogamama#1843",
-            discord_icon: discord_icon,
-            battlenet_icon:  battlenet_icon       
-        },
-        ContactInfo {
-            is_gm: false,
-            name: "Infi",
-            discord: "windfi",
-            battlenet: "Infi#11812",
-            discord_icon: discord_icon,
-            battlenet_icon:  battlenet_icon       
-        },
+    // guild masters first, then officers, each in members.json order.
+    let mut contacts: Vec<&Member> = members
+        .members
+        .iter()
+        .filter(|member| member.rank.is_contact() && member.discord.is_some())
+        .collect();
+    contacts.sort_by_key(|member| member.rank != Rank::GuildMaster);
 
-    ];
-    contact_info
+    contacts
+        .into_iter()
+        .map(|member| ContactInfo {
+            is_gm: member.rank == Rank::GuildMaster,
+            name: member.display_name.clone(),
+            discord: member.discord.clone().unwrap_or_default(),
+            battlenet: member.battlenet.clone(),
+            discord_icon,
+            battlenet_icon,
+        })
+        .collect()
 }
@@ -0,0 +1,557 @@
+use askama_axum::Template;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::Form;
+use base64::Engine;
+use chrono::{DateTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use worker::Env;
+
+use crate::discord::{post_webhook, DeliveryStatus, Embed, EmbedField, RetryPolicy, WebhookMessage};
+use crate::http::{FetchClient, HttpClient};
+use crate::storage::{ContentStore, Store};
+use crate::realm::{armory_url, normalize_realm, raiderio_url, warcraftlogs_url};
+use crate::time::Region;
+use crate::wow::{PlayerClass, Spec};
+use crate::{error_page, BaseTemplate};
+
+// +-------------------+
+// | Guild Application |
+// +-------------------+
+// /application posts here instead of Google Forms. Each application is one json object
+// in R2 under applications/, officers read them at /applications.
+
+const APPLICATIONS_PREFIX: &str = "applications/";
+/// wrangler secret, officers sign in to /applications with any username and this password.
+const OFFICER_PASSWORD: &str = "OFFICER_PASSWORD";
+/// wrangler secret, new applications are posted to this discord webhook when it is set.
+const APPLICATIONS_WEBHOOK: &str = "DISCORD_APPLICATIONS_WEBHOOK";
+
+const RAIDERIO_PREFIX: &str = "https://raider.io/characters/";
+const WCL_PREFIXES: [&str; 2] = [
+    "https://www.warcraftlogs.com/character/",
+    "https://warcraftlogs.com/character/",
+];
+/// keeps the embed's "Links" field under Discord's 1024 characters with the armory link.
+const MAX_LINK_CHARS: usize = 256;
+
+/// every day, monday first, for the availability checkboxes.
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// A validated application, as stored in R2.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Application {
+    pub id: String,
+    pub submitted_at: DateTime<Utc>,
+    pub character: String,
+    pub realm: String,
+    /// applications from before regions were asked for are all us.
+    #[serde(default)]
+    pub region: Region,
+    pub spec: Spec,
+    pub discord: String,
+    pub raiderio_url: Option<String>,
+    pub wcl_url: Option<String>,
+    /// days they can raid.
+    pub availability: Vec<Weekday>,
+    pub experience: String,
+    /// discord webhook result, None until a post was tried.
+    #[serde(default)]
+    pub notification: Option<DeliveryStatus>,
+}
+
+impl Application {
+    pub fn storage_key(&self) -> String {
+        format!("{}{}.json", APPLICATIONS_PREFIX, self.id)
+    }
+
+    pub fn availability_label(&self) -> String {
+        self.availability
+            .iter()
+            .map(|day| day.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn armory_url(&self) -> String {
+        armory_url(self.region, &self.realm, &self.character)
+    }
+
+    pub fn realm_label(&self) -> String {
+        self.region.realm_label(&self.realm)
+    }
+
+    /// the link they gave us, or their raider.io page.
+    pub fn raiderio_link(&self) -> String {
+        self.raiderio_url
+            .clone()
+            .unwrap_or_else(|| raiderio_url(self.region, &self.realm, &self.character))
+    }
+
+    /// the link they gave us, or their warcraftlogs page.
+    pub fn wcl_link(&self) -> String {
+        self.wcl_url
+            .clone()
+            .unwrap_or_else(|| warcraftlogs_url(self.region, &self.realm, &self.character))
+    }
+
+    /// the officer channel's embed for this application.
+    pub fn discord_message(&self) -> WebhookMessage {
+        let mut experience: String = self.experience.chars().take(1000).collect();
+        if experience.len() < self.experience.len() {
+            experience.push('…');
+        }
+        let links = format!(
+            "[Armory]({}) · [Raider.io]({}) · [Warcraft Logs]({})",
+            self.armory_url(),
+            self.raiderio_link(),
+            self.wcl_link()
+        );
+        WebhookMessage {
+            content: None,
+            embeds: vec![Embed {
+                title: format!("New application: {}-{}", self.character, self.realm),
+                description: Some(experience),
+                url: Some(self.armory_url()),
+                color: Some(self.spec.class().color_value()),
+                fields: vec![
+                    EmbedField::new("Spec", self.spec.label(), true),
+                    EmbedField::new("Realm", self.realm_label(), true),
+                    EmbedField::new("Discord", self.discord.as_str(), true),
+                    EmbedField::new("Availability", self.availability_label(), false),
+                    EmbedField::new("Links", links, false),
+                ],
+                timestamp: Some(self.submitted_at),
+            }],
+        }
+    }
+}
+
+/// What the applicant typed, kept as text so the form can be shown again with their answers.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ApplicationForm {
+    pub character: String,
+    pub realm: String,
+    /// "us" or "eu", us when left out.
+    pub region: String,
+    pub spec: String,
+    pub discord: String,
+    pub raiderio_url: String,
+    pub wcl_url: String,
+    pub experience: String,
+    // checked boxes are sent as "on", unchecked ones are left out.
+    pub mon: Option<String>,
+    pub tue: Option<String>,
+    pub wed: Option<String>,
+    pub thu: Option<String>,
+    pub fri: Option<String>,
+    pub sat: Option<String>,
+    pub sun: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ApplicationForm {
+    pub fn has_day(&self, day: &Weekday) -> bool {
+        let checkbox = match day {
+            Weekday::Mon => &self.mon,
+            Weekday::Tue => &self.tue,
+            Weekday::Wed => &self.wed,
+            Weekday::Thu => &self.thu,
+            Weekday::Fri => &self.fri,
+            Weekday::Sat => &self.sat,
+            Weekday::Sun => &self.sun,
+        };
+        checkbox.is_some()
+    }
+
+    /// Check every field and build the application, or return every problem found.
+    pub fn validate(&self, submitted_at: DateTime<Utc>) -> Result<Application, Vec<FieldError>> {
+        let mut errors = Vec::new();
+        let mut error = |field: &'static str, message: &str| {
+            errors.push(FieldError {
+                field,
+                message: message.to_string(),
+            })
+        };
+
+        let character = self.character.trim();
+        if !(2..=12).contains(&character.chars().count()) || !character.chars().all(char::is_alphabetic) {
+            error("character", "Character names are 2 to 12 letters.");
+        }
+        let realm = self.realm.trim();
+        if realm.is_empty() || realm.chars().count() > 64 {
+            error("realm", "Enter the realm your character is on.");
+        }
+        let region = match self.region.trim() {
+            "" => Region::Us,
+            region => region.parse::<Region>().unwrap_or_else(|_| {
+                error("region", "Pick US or EU.");
+                Region::Us
+            }),
+        };
+        let spec = self.spec.parse::<Spec>().ok();
+        if spec.is_none() {
+            error("spec", "Pick the spec you want to raid as.");
+        }
+        let discord = self.discord.trim();
+        if !(2..=32).contains(&discord.chars().count()) {
+            error("discord", "Enter your Discord username so we can reach you.");
+        }
+        let raiderio_url = optional_link(&self.raiderio_url);
+        if raiderio_url.as_ref().is_some_and(|url| !url.starts_with(RAIDERIO_PREFIX)) {
+            error("raiderio_url", "Raider.io links start with https://raider.io/characters/");
+        } else if raiderio_url.as_ref().is_some_and(|url| url.chars().count() > MAX_LINK_CHARS) {
+            error("raiderio_url", "That Raider.io link is too long, link your character page.");
+        }
+        let wcl_url = optional_link(&self.wcl_url);
+        if wcl_url
+            .as_ref()
+            .is_some_and(|url| !WCL_PREFIXES.iter().any(|prefix| url.starts_with(prefix)))
+        {
+            error("wcl_url", "Warcraft Logs links start with https://www.warcraftlogs.com/character/");
+        } else if wcl_url.as_ref().is_some_and(|url| url.chars().count() > MAX_LINK_CHARS) {
+            error("wcl_url", "That Warcraft Logs link is too long, link your character page.");
+        }
+        let availability: Vec<Weekday> = WEEKDAYS.into_iter().filter(|day| self.has_day(day)).collect();
+        if availability.is_empty() {
+            error("availability", "Pick at least one day you can raid.");
+        }
+        let experience = self.experience.trim();
+        if !(20..=4000).contains(&experience.chars().count()) {
+            error("experience", "Tell us about your raiding experience (20 to 4000 characters).");
+        }
+
+        match spec {
+            Some(spec) if errors.is_empty() => Ok(Application {
+                id: format!("{}-{}", submitted_at.timestamp_millis(), character.to_lowercase()),
+                submitted_at,
+                character: character.to_string(),
+                realm: normalize_realm(realm),
+                region,
+                spec,
+                discord: discord.to_string(),
+                raiderio_url,
+                wcl_url,
+                availability,
+                experience: experience.to_string(),
+                notification: None,
+            }),
+            _ => Err(errors),
+        }
+    }
+}
+
+fn optional_link(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+// +---------+
+// | Storage |
+// +---------+
+
+pub async fn save_application<S: ContentStore>(store: &S, application: &Application) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(application)
+        .map_err(|e| format!("Failed to serialize application {}: {}", application.id, e))?;
+    store
+        .put(&application.storage_key(), &json, "application/json")
+        .await
+}
+
+/// Every stored application, newest first. Unreadable ones are logged and skipped.
+pub async fn list_applications<S: ContentStore>(store: &S) -> Result<Vec<Application>, String> {
+    let mut applications = Vec::new();
+    for key in store.list(APPLICATIONS_PREFIX).await? {
+        match store.get(&key).await? {
+            Some(object) => match serde_json::from_str::<Application>(&object.body) {
+                Ok(application) => applications.push(application),
+                Err(e) => console_log!("Skipping unreadable application {}: {}", key, e),
+            },
+            None => console_log!("Application {} was listed but is gone", key),
+        }
+    }
+    applications.sort_by_key(|application| std::cmp::Reverse(application.submitted_at));
+    Ok(applications)
+}
+
+/// Post the application to the officers' webhook and save how it went with the application.
+pub async fn notify_officers<S: ContentStore, H: HttpClient>(
+    store: &S,
+    client: &H,
+    webhook_url: Option<&str>,
+    application: &mut Application,
+    retry: &RetryPolicy,
+) -> Result<(), String> {
+    let status = match webhook_url {
+        Some(url) => post_webhook(client, url, &application.discord_message(), retry).await,
+        None => DeliveryStatus::Skipped,
+    };
+    if let DeliveryStatus::Failed { error, .. } = &status {
+        console_log!("Application {} was not posted to Discord: {}", application.id, error);
+    }
+    application.notification = Some(status);
+    save_application(store, application).await
+}
+
+// +-------+
+// | Pages |
+// +-------+
+
+#[derive(Template)]
+#[template(path = "apply.html")]
+struct ApplyTemplate {
+    base: BaseTemplate,
+    form: ApplicationForm,
+    errors: Vec<FieldError>,
+    /// set once the application is saved.
+    submitted: Option<String>,
+    classes: [PlayerClass; 13],
+    regions: [Region; 2],
+    weekdays: [Weekday; 7],
+}
+
+impl ApplyTemplate {
+    fn new(form: ApplicationForm, errors: Vec<FieldError>, submitted: Option<String>) -> Self {
+        Self {
+            base: BaseTemplate::new(false),
+            form,
+            errors,
+            submitted,
+            classes: PlayerClass::ALL,
+            regions: Region::ALL,
+            weekdays: WEEKDAYS,
+        }
+    }
+
+    fn field_error(&self, field: &str) -> Option<&str> {
+        self.errors
+            .iter()
+            .find(|error| error.field == field)
+            .map(|error| error.message.as_str())
+    }
+}
+
+pub async fn apply_page() -> Html<String> {
+    let template = ApplyTemplate::new(ApplicationForm::default(), Vec::new(), None);
+    Html(template.render().unwrap())
+}
+
+#[worker::send]
+pub async fn submit_handler(State(env): State<Env>, Form(form): Form<ApplicationForm>) -> Response {
+    let mut application = match form.validate(Utc::now()) {
+        Ok(application) => application,
+        Err(errors) => {
+            let template = ApplyTemplate::new(form, errors, None);
+            return (StatusCode::UNPROCESSABLE_ENTITY, Html(template.render().unwrap())).into_response();
+        }
+    };
+
+    let store = Store::from_env(&env);
+    if let Err(e) = save_application(&store, &application).await {
+        console_log!("{}", e);
+        return error_page(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Application not sent",
+            "We couldn't save your application, please try again in a few minutes or message an officer on Discord.",
+        );
+    }
+
+    // the application is already saved, a failed post only shows up on /applications.
+    let webhook_url = env.secret(APPLICATIONS_WEBHOOK).ok().map(|secret| secret.to_string());
+    let notified = notify_officers(
+        &store,
+        &FetchClient,
+        webhook_url.as_deref(),
+        &mut application,
+        &RetryPolicy::default(),
+    )
+    .await;
+    if let Err(e) = notified {
+        console_log!("Failed to record webhook status for {}: {}", application.id, e);
+    }
+
+    let template = ApplyTemplate::new(ApplicationForm::default(), Vec::new(), Some(application.character));
+    Html(template.render().unwrap()).into_response()
+}
+
+#[derive(Template)]
+#[template(path = "applications.html")]
+struct ApplicationsTemplate {
+    base: BaseTemplate,
+    applications: Vec<Application>,
+}
+
+/// Officer listing, behind basic auth with the OFFICER_PASSWORD secret.
+#[worker::send]
+pub async fn applications_page(State(env): State<Env>, headers: HeaderMap) -> Response {
+    let password = match env.secret(OFFICER_PASSWORD) {
+        Ok(password) => password.to_string(),
+        Err(_) => {
+            return error_page(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Applications unavailable",
+                "OFFICER_PASSWORD is not set for this worker.",
+            )
+        }
+    };
+    if !is_officer(&headers, &password) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Basic realm=\"officers\"")],
+            "Officers only",
+        )
+            .into_response();
+    }
+
+    let store = Store::from_env(&env);
+    match list_applications(&store).await {
+        Ok(applications) => {
+            let template = ApplicationsTemplate {
+                base: BaseTemplate::new(false),
+                applications,
+            };
+            let mut response = Html(template.render().unwrap()).into_response();
+            response
+                .headers_mut()
+                .insert(header::CACHE_CONTROL, "no-store".parse().unwrap());
+            response
+        }
+        Err(e) => error_page(StatusCode::SERVICE_UNAVAILABLE, "Applications unavailable", &e),
+    }
+}
+
+// "Authorization: Basic base64(user:password)", any user name is accepted.
+fn is_officer(headers: &HeaderMap, password: &str) -> bool {
+    let Some(encoded) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+    else {
+        return false;
+    };
+    let Ok(decoded) = base64::engine::general_purpose::STANDARD.decode(encoded.trim()) else {
+        return false;
+    };
+    let Some((_, given)) = std::str::from_utf8(&decoded).ok().and_then(|value| value.split_once(':')) else {
+        return false;
+    };
+    // compare every byte so the time taken doesn't leak how much matched.
+    given.len() == password.len()
+        && given
+            .bytes()
+            .zip(password.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStore;
+    use crate::testing::{block_on, StubClient};
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    fn form(character: &str) -> ApplicationForm {
+        ApplicationForm {
+            character: character.to_string(),
+            realm: "area-52".to_string(),
+            spec: "Devastation".to_string(),
+            discord: "nuzz".to_string(),
+            experience: "Cutting edge in The War Within seasons 1 and 2.".to_string(),
+            tue: Some("on".to_string()),
+            thu: Some("on".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn submitted_applications_are_listed_newest_first() {
+        let store = MemoryStore::new();
+        let first = form("Indico").validate(Utc.with_ymd_and_hms(2025, 10, 14, 1, 0, 0).unwrap()).unwrap();
+        let second = form("Notshodo").validate(Utc.with_ymd_and_hms(2025, 10, 15, 1, 0, 0).unwrap()).unwrap();
+        block_on(save_application(&store, &first)).unwrap();
+        block_on(save_application(&store, &second)).unwrap();
+
+        let listed = block_on(list_applications(&store)).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|application| application.id.as_str()).collect();
+        assert_eq!(ids, [second.id.as_str(), first.id.as_str()]);
+        assert_eq!(listed[1].realm, "Area 52");
+        assert_eq!(listed[1].availability, [Weekday::Tue, Weekday::Thu]);
+    }
+
+    #[test]
+    fn saving_without_r2_fails() {
+        let store = Store::bundled(false);
+        let application = form("Indico").validate(Utc::now()).unwrap();
+
+        assert!(block_on(save_application(&store, &application)).is_err());
+        assert!(block_on(list_applications(&store)).unwrap().is_empty());
+
+        let store = Store::bundled(true);
+        block_on(save_application(&store, &application)).unwrap();
+        assert_eq!(block_on(list_applications(&store)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn long_links_are_rejected() {
+        let mut form = form("Indico");
+        form.raiderio_url = format!("{}us/area-52/indico?{}", RAIDERIO_PREFIX, "x".repeat(MAX_LINK_CHARS));
+        form.wcl_url = format!("{}us/area-52/indico?{}", WCL_PREFIXES[0], "x".repeat(MAX_LINK_CHARS));
+
+        let fields: Vec<&str> = form.validate(Utc::now()).unwrap_err().iter().map(|error| error.field).collect();
+        assert_eq!(fields, ["raiderio_url", "wcl_url"]);
+    }
+
+    #[test]
+    fn links_field_fits_discord() {
+        let mut form = form("Indico");
+        form.raiderio_url = format!("{}{}", RAIDERIO_PREFIX, "x".repeat(MAX_LINK_CHARS - RAIDERIO_PREFIX.len()));
+        form.wcl_url = format!("{}{}", WCL_PREFIXES[0], "x".repeat(MAX_LINK_CHARS - WCL_PREFIXES[0].len()));
+        form.realm = "x".repeat(64);
+        let message = form.validate(Utc::now()).unwrap().discord_message();
+
+        let links = message.embeds[0].fields.iter().find(|field| field.name == "Links").unwrap();
+        assert!(links.value.chars().count() <= 1024, "{} characters", links.value.chars().count());
+    }
+
+    #[test]
+    fn delivery_status_is_saved_with_the_application() {
+        let store = MemoryStore::new();
+        let client = StubClient::new(&[(500, "oops"), (404, "Unknown Webhook")]);
+        let retry = RetryPolicy {
+            attempts: 3,
+            backoff: Duration::ZERO,
+        };
+        let mut application = form("Indico").validate(Utc::now()).unwrap();
+        block_on(notify_officers(&store, &client, Some("https://discord.test/webhook"), &mut application, &retry)).unwrap();
+
+        let listed = block_on(list_applications(&store)).unwrap();
+        match &listed[0].notification {
+            Some(DeliveryStatus::Failed { attempts, error, .. }) => {
+                assert_eq!(*attempts, 2);
+                assert_eq!(error, "discord answered 404: Unknown Webhook");
+            }
+            status => panic!("expected a failed delivery, got {:?}", status),
+        }
+
+        block_on(notify_officers(&store, &client, None, &mut application, &retry)).unwrap();
+        let listed = block_on(list_applications(&store)).unwrap();
+        assert!(matches!(listed[0].notification, Some(DeliveryStatus::Skipped)));
+    }
+}
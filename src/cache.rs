@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use worker::{Cache, Headers, Response};
+
+// +-----------------+
+// | Last Known Good |
+// +-----------------+
+// The last successful copy of a document, kept so an R2 outage doesn't blank the page.
+// Stored with the Workers Cache API under a url that is never served to browsers.
+
+const LAST_GOOD_URL: &str = "https://seemsgood.org/__last-good/";
+// how long the cache api may keep a copy. long enough to ride out an outage.
+const LAST_GOOD_MAX_AGE: u32 = 60 * 60 * 24 * 30;
+const STORED_AT_HEADER: &str = "x-stored-at";
+
+#[derive(Debug, Clone)]
+pub struct CachedCopy {
+    pub body: String,
+    pub stored_at: DateTime<Utc>,
+}
+
+impl CachedCopy {
+    /// seconds since the copy was stored, used for the Age header.
+    pub fn age_seconds(&self, now: DateTime<Utc>) -> i64 {
+        (now - self.stored_at).num_seconds().max(0)
+    }
+}
+
+pub trait LastGoodCache {
+    async fn get(&self, name: &str) -> Result<Option<CachedCopy>, String>;
+    async fn put(&self, name: &str, body: &str, stored_at: DateTime<Utc>) -> Result<(), String>;
+}
+
+// the zone's default cache.
+#[derive(Default)]
+pub struct WorkerCache {
+    cache: Cache,
+}
+
+impl LastGoodCache for WorkerCache {
+    async fn get(&self, name: &str) -> Result<Option<CachedCopy>, String> {
+        let key = format!("{}{}", LAST_GOOD_URL, name);
+        let mut response = match self
+            .cache
+            .get(key, true)
+            .await
+            .map_err(|e| format!("Failed to read cached {}: {:?}", name, e))?
+        {
+            Some(response) => response,
+            None => return Ok(None),
+        };
+
+        let stored_at = response
+            .headers()
+            .get(STORED_AT_HEADER)
+            .ok()
+            .flatten()
+            .and_then(|stored_at| DateTime::parse_from_rfc3339(&stored_at).ok())
+            .map(|stored_at| stored_at.with_timezone(&Utc))
+            .ok_or_else(|| format!("Cached {} has no {} header", name, STORED_AT_HEADER))?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read cached {}: {:?}", name, e))?;
+
+        Ok(Some(CachedCopy { body, stored_at }))
+    }
+
+    async fn put(&self, name: &str, body: &str, stored_at: DateTime<Utc>) -> Result<(), String> {
+        let headers = Headers::new();
+        let cache_control = format!("public, max-age={}", LAST_GOOD_MAX_AGE);
+        headers
+            .set("cache-control", &cache_control)
+            .and_then(|_| headers.set(STORED_AT_HEADER, &stored_at.to_rfc3339()))
+            .map_err(|e| format!("Failed to build cached {}: {:?}", name, e))?;
+
+        let response = Response::ok(body)
+            .map_err(|e| format!("Failed to build cached {}: {:?}", name, e))?
+            .with_headers(headers);
+        self.cache
+            .put(format!("{}{}", LAST_GOOD_URL, name), response)
+            .await
+            .map_err(|e| format!("Failed to cache {}: {:?}", name, e))
+    }
+}
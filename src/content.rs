@@ -0,0 +1,324 @@
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, Utc};
+use comrak::{markdown_to_html, ComrakOptions};
+use worker::Env;
+
+use crate::cache::{LastGoodCache, WorkerCache};
+use crate::storage::{ContentStore, Store, StoredObject};
+use crate::{enable_extensions, ASSETS_DIR};
+
+// +------------------+
+// | Content Manifest |
+// +------------------+
+// Every R2 document the worker is allowed to read is listed here.
+// Served at /content/{name}. Adding a new document means adding one entry.
+// Keys are read from the R2_CONTENT binding (see storage.rs).
+
+/// how a document is returned to the browser.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContentKind {
+    Json,
+    /// rendered to html with comrak before it is served.
+    Markdown,
+    /// served as-is with the given content type.
+    Raw(&'static str),
+}
+
+impl ContentKind {
+    fn content_type(&self) -> &'static str {
+        match self {
+            ContentKind::Json => "application/json",
+            ContentKind::Markdown => "text/html; charset=utf-8",
+            ContentKind::Raw(content_type) => content_type,
+        }
+    }
+
+    // body served when R2 and the fallback asset are both unavailable.
+    fn empty_body(&self) -> &'static str {
+        match self {
+            ContentKind::Json => "{}",
+            ContentKind::Markdown => "<p>Content unavailable.</p>",
+            ContentKind::Raw(_) => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CachePolicy {
+    NoStore,
+    MaxAge(u32),
+}
+
+impl CachePolicy {
+    fn header_value(&self) -> String {
+        match self {
+            CachePolicy::NoStore => "no-cache, no-store, must-revalidate".to_string(),
+            CachePolicy::MaxAge(seconds) => format!("public, max-age={}", seconds),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ContentEntry {
+    /// name used in /content/{name}
+    pub name: &'static str,
+    /// object key in the R2_CONTENT bucket
+    pub key: &'static str,
+    pub kind: ContentKind,
+    /// path in ASSETS_DIR served when R2 can't be reached.
+    pub fallback: Option<&'static str>,
+    pub cache: CachePolicy,
+    /// keep the last successful copy and serve it (with an Age header) when R2 fails.
+    pub keep_last_good: bool,
+}
+
+pub static CONTENT_MANIFEST: &[ContentEntry] = &[
+    ContentEntry {
+        name: "events.json",
+        key: "content/events.json",
+        kind: ContentKind::Json,
+        fallback: None,
+        cache: CachePolicy::NoStore,
+        keep_last_good: true,
+    },
+    // written by the wowaudit refresh cron (see refresh.rs), like events.json.
+    ContentEntry {
+        name: "characters.json",
+        key: "content/characters.json",
+        kind: ContentKind::Json,
+        fallback: None,
+        cache: CachePolicy::NoStore,
+        keep_last_good: true,
+    },
+    ContentEntry {
+        name: "progress.json",
+        key: "content/progress.json",
+        kind: ContentKind::Json,
+        fallback: None,
+        cache: CachePolicy::NoStore,
+        keep_last_good: true,
+    },
+    ContentEntry {
+        name: "rosters.json",
+        key: "content/rosters.json",
+        kind: ContentKind::Json,
+        fallback: Some("assets/rosters.json"),
+        cache: CachePolicy::MaxAge(300),
+        keep_last_good: false,
+    },
+    ContentEntry {
+        name: "members.json",
+        key: "content/members.json",
+        kind: ContentKind::Json,
+        fallback: Some("assets/members.json"),
+        cache: CachePolicy::MaxAge(300),
+        keep_last_good: false,
+    },
+    ContentEntry {
+        name: "raids.json",
+        key: "content/raids.json",
+        kind: ContentKind::Json,
+        fallback: Some("assets/raids.json"),
+        cache: CachePolicy::MaxAge(300),
+        keep_last_good: false,
+    },
+    ContentEntry {
+        name: "seasons.json",
+        key: "content/seasons.json",
+        kind: ContentKind::Json,
+        fallback: Some("assets/seasons.json"),
+        cache: CachePolicy::MaxAge(300),
+        keep_last_good: false,
+    },
+    ContentEntry {
+        name: "raider-expectations.md",
+        key: "content/raider-expectations.md",
+        kind: ContentKind::Markdown,
+        fallback: None,
+        cache: CachePolicy::NoStore,
+        keep_last_good: false,
+    },
+    // unrendered copy of the expectations, for tools that want the markdown itself.
+    ContentEntry {
+        name: "raider-expectations-source.md",
+        key: "content/raider-expectations.md",
+        kind: ContentKind::Raw("text/markdown; charset=utf-8"),
+        fallback: None,
+        cache: CachePolicy::NoStore,
+        keep_last_good: false,
+    },
+];
+
+/// find an allowed document by name. anything not in the manifest is rejected.
+pub fn lookup(name: &str) -> Option<&'static ContentEntry> {
+    CONTENT_MANIFEST.iter().find(|entry| entry.name == name)
+}
+
+impl ContentEntry {
+    /// the bundled copy of this document, if it has one.
+    pub fn fallback_body(&self) -> Option<&'static str> {
+        self.fallback
+            .and_then(|path| ASSETS_DIR.get_file(path))
+            .and_then(|file| file.contents_utf8())
+    }
+}
+
+// +-----------------+
+// | Content Loading |
+// +-----------------+
+
+/// Read the document from the store, falling back on the entry's bundled asset.
+pub async fn load<S: ContentStore>(store: &S, entry: &ContentEntry) -> Result<StoredObject, String> {
+    let error = match store.get(entry.key).await {
+        Ok(Some(object)) => return Ok(object),
+        Ok(None) => format!("{} does not exist", entry.key),
+        Err(e) => e,
+    };
+    console_log!("Error loading {}: {}", entry.key, error);
+    entry
+        .fallback_body()
+        .map(StoredObject::from_body)
+        .ok_or(error)
+}
+
+// +------------------+
+// | Content Handlers |
+// +------------------+
+
+/// A document ready to serve. `age` is set when it is a last known good copy.
+pub struct Latest {
+    pub object: StoredObject,
+    pub age: Option<i64>,
+}
+
+impl Latest {
+    /// When the document was last written as far as we can tell: R2's upload time, or for a last
+    /// known good copy when R2 was last read. None for the bundled asset.
+    pub fn updated_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.age {
+            Some(age) => Some(now - Duration::seconds(age)),
+            None => self.object.uploaded,
+        }
+    }
+}
+
+/// Read the newest copy of a document: R2, then the last known good copy, then the bundled asset.
+/// Successful R2 reads refresh the last known good copy.
+pub async fn load_latest<S: ContentStore, C: LastGoodCache>(
+    store: &S,
+    last_good: &C,
+    entry: &ContentEntry,
+) -> Option<Latest> {
+    let now = Utc::now();
+    match store.get(entry.key).await {
+        Ok(Some(object)) => {
+            if entry.keep_last_good {
+                if let Err(e) = last_good.put(entry.name, &object.body, now).await {
+                    console_log!("{}", e);
+                }
+            }
+            return Some(Latest { object, age: None });
+        }
+        Ok(None) => console_log!("Error loading {}: does not exist", entry.key),
+        Err(e) => console_log!("Error loading {}: {}", entry.key, e),
+    }
+
+    if entry.keep_last_good {
+        match last_good.get(entry.name).await {
+            Ok(Some(copy)) => {
+                return Some(Latest {
+                    age: Some(copy.age_seconds(now)),
+                    object: StoredObject::from_body(copy.body),
+                })
+            }
+            Ok(None) => {}
+            Err(e) => console_log!("{}", e),
+        }
+    }
+
+    entry.fallback_body().map(|body| Latest {
+        object: StoredObject::from_body(body),
+        age: None,
+    })
+}
+
+// Request  | /content/{name} -> lookup -> load_latest -> |R2, last known good, or fallback|
+// Response | /content/{name} <- render <- |json, markdown->html, raw|
+pub async fn serve<S: ContentStore, C: LastGoodCache>(
+    store: &S,
+    last_good: &C,
+    entry: &ContentEntry,
+) -> Response {
+    let (age, object) = match load_latest(store, last_good, entry).await {
+        Some(latest) => (latest.age, Some(latest.object)),
+        None => (None, None),
+    };
+
+    // nothing in R2, the cache, or the bundle.
+    let (status, object) = match object {
+        Some(object) => (StatusCode::OK, object),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            StoredObject::from_body(entry.kind.empty_body()),
+        ),
+    };
+
+    let body = match entry.kind {
+        ContentKind::Markdown => {
+            let mut options = ComrakOptions::default();
+            enable_extensions(&mut options);
+            format!(
+                r#"<div class="markdown-body">{}</div>"#,
+                markdown_to_html(&object.body, &options)
+            )
+        }
+        ContentKind::Json | ContentKind::Raw(_) => object.body,
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(entry.kind.content_type()));
+    if let Ok(cache) = HeaderValue::from_str(&entry.cache.header_value()) {
+        headers.insert(header::CACHE_CONTROL, cache);
+    }
+    if let Some(age) = age {
+        headers.insert(header::AGE, HeaderValue::from(age));
+    }
+    if let Some(etag) = object.etag.and_then(|etag| HeaderValue::from_str(&etag).ok()) {
+        headers.insert(header::ETAG, etag);
+    }
+    if let Some(uploaded) = object.uploaded {
+        let last_modified = uploaded.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        if let Ok(last_modified) = HeaderValue::from_str(&last_modified) {
+            headers.insert(header::LAST_MODIFIED, last_modified);
+        }
+    }
+
+    (status, headers, body).into_response()
+}
+
+/// GET /content/{name}
+#[worker::send]
+pub async fn content_handler(State(env): State<Env>, Path(name): Path<String>) -> Response {
+    match lookup(&name) {
+        Some(entry) => serve(&Store::from_env(&env), &WorkerCache::default(), entry).await,
+        None => (StatusCode::NOT_FOUND, "Content not found").into_response(),
+    }
+}
+
+// Routes the templates already call, kept as aliases of /content/{name}.
+#[worker::send]
+pub async fn events_handler(state: State<Env>) -> Response {
+    content_handler(state, Path("events.json".to_string())).await
+}
+
+#[worker::send]
+pub async fn progress_handler(state: State<Env>) -> Response {
+    content_handler(state, Path("progress.json".to_string())).await
+}
+
+#[worker::send]
+pub async fn expectations_handler(state: State<Env>) -> Response {
+    content_handler(state, Path("raider-expectations.md".to_string())).await
+}
@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::http::HttpClient;
+
+// +-----------------+
+// | Discord Webhook |
+// +-----------------+
+// Just enough of https://discord.com/developers/docs/resources/webhook#execute-webhook
+// to post embeds.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub embeds: Vec<Embed>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Embed {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 0xRRGGBB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<EmbedField>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
+
+impl EmbedField {
+    pub fn new(name: &str, value: impl Into<String>, inline: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.into(),
+            inline,
+        }
+    }
+}
+
+// +----------+
+// | Delivery |
+// +----------+
+
+/// How a webhook post went, kept with whatever it was about.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Delivered { attempts: u32, at: DateTime<Utc> },
+    Failed { attempts: u32, error: String, at: DateTime<Utc> },
+    /// no webhook url is configured.
+    Skipped,
+}
+
+impl std::fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryStatus::Delivered { attempts, .. } => write!(f, "posted to Discord ({} attempt(s))", attempts),
+            DeliveryStatus::Failed { attempts, error, .. } => {
+                write!(f, "Discord post failed after {} attempt(s): {}", attempts, error)
+            }
+            DeliveryStatus::Skipped => write!(f, "no Discord webhook configured"),
+        }
+    }
+}
+
+pub struct RetryPolicy {
+    pub attempts: u32,
+    /// wait before the 2nd attempt, doubled after each failure. zero never waits.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Post a message, retrying connection errors, 429s and 5xx. Other 4xx mean the message or url
+/// is wrong and are not retried.
+pub async fn post_webhook<H: HttpClient>(
+    client: &H,
+    url: &str,
+    message: &WebhookMessage,
+    retry: &RetryPolicy,
+) -> DeliveryStatus {
+    let body = match serde_json::to_string(message) {
+        Ok(body) => body,
+        Err(e) => {
+            return DeliveryStatus::Failed {
+                attempts: 0,
+                error: format!("Failed to serialize webhook message: {}", e),
+                at: Utc::now(),
+            }
+        }
+    };
+
+    let mut backoff = retry.backoff;
+    let mut error = String::new();
+    for attempt in 1..=retry.attempts.max(1) {
+        if attempt > 1 && !backoff.is_zero() {
+            worker::Delay::from(backoff).await;
+            backoff *= 2;
+        }
+        match client.post_json(url, &body).await {
+            Ok(response) if response.is_success() => {
+                return DeliveryStatus::Delivered {
+                    attempts: attempt,
+                    at: Utc::now(),
+                }
+            }
+            Ok(response) => {
+                error = format!("discord answered {}: {}", response.status, response.body);
+                if response.status != 429 && response.status < 500 {
+                    return DeliveryStatus::Failed {
+                        attempts: attempt,
+                        error,
+                        at: Utc::now(),
+                    };
+                }
+            }
+            Err(e) => error = e,
+        }
+        console_log!("Webhook attempt {} failed: {}", attempt, error);
+    }
+    DeliveryStatus::Failed {
+        attempts: retry.attempts.max(1),
+        error,
+        at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{block_on, StubClient};
+
+    const URL: &str = "https://discord.com/api/webhooks/1/token";
+
+    fn message() -> WebhookMessage {
+        WebhookMessage {
+            content: Some("hello".to_string()),
+            embeds: Vec::new(),
+        }
+    }
+
+    fn post(client: &StubClient, attempts: u32) -> DeliveryStatus {
+        let retry = RetryPolicy {
+            attempts,
+            backoff: Duration::ZERO,
+        };
+        block_on(post_webhook(client, URL, &message(), &retry))
+    }
+
+    #[test]
+    fn retries_server_errors_and_rate_limits() {
+        let client = StubClient::new(&[(502, "bad gateway"), (429, "slow down"), (204, "")]);
+
+        assert!(matches!(post(&client, 3), DeliveryStatus::Delivered { attempts: 3, .. }));
+        assert_eq!(client.request_count(), 3);
+    }
+
+    #[test]
+    fn retries_connection_errors() {
+        let client = StubClient::default();
+        client.fail("connection reset");
+        client.respond(200, "");
+
+        assert!(matches!(post(&client, 3), DeliveryStatus::Delivered { attempts: 2, .. }));
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let client = StubClient::new(&[(404, "Unknown Webhook")]);
+
+        match post(&client, 3) {
+            DeliveryStatus::Failed { attempts, error, .. } => {
+                assert_eq!(attempts, 1);
+                assert_eq!(error, "discord answered 404: Unknown Webhook");
+            }
+            status => panic!("expected a failure, got {:?}", status),
+        }
+        assert_eq!(client.request_count(), 1);
+    }
+
+    #[test]
+    fn gives_up_after_the_last_attempt() {
+        let client = StubClient::new(&[(500, "oops"), (503, "down"), (500, "still down")]);
+
+        match post(&client, 3) {
+            DeliveryStatus::Failed { attempts, error, .. } => {
+                assert_eq!(attempts, 3);
+                assert_eq!(error, "discord answered 500: still down");
+            }
+            status => panic!("expected a failure, got {:?}", status),
+        }
+        // the stub panics on a 4th request.
+        assert_eq!(client.request_count(), 3);
+    }
+}
@@ -1,48 +1,100 @@
-use axum::response::Html;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
 use askama_axum::Template;
-use crate::BaseTemplate;
+use worker::Env;
+use crate::{error_page, BaseTemplate};
+use crate::player_metadata::{load_members, MemberDocument};
+use crate::storage::Store;
+use crate::wow::{class_label, PlayerClass, Spec};
+
+const SIM_URL: &str = "https://r2.seemsgood.org/roster/";
+
+/// a sim report in r2, named after the character that was simmed.
+struct Sim {
+    /// id in members.json.
+    member: &'static str,
+    character: &'static str,
+    /// the spec that was simmed, when we know it.
+    spec: Option<Spec>,
+}
+
+const SIMS: &[Sim] = &[
+    Sim { member: "nuzz", character: "Nuzzsin", spec: Some(Spec::Assassination) },
+    Sim { member: "infi", character: "Infilicious", spec: None },
+    Sim { member: "shodo", character: "Notshodo", spec: Some(Spec::Devastation) },
+    Sim { member: "chint", character: "Chinterfel", spec: Some(Spec::Havoc) },
+    Sim { member: "roger", character: "Bigtittyrog", spec: None },
+    Sim { member: "chuubers", character: "Chuubers", spec: None },
+    Sim { member: "delulu", character: "Delusionil", spec: Some(Spec::Shadow) },
+    Sim { member: "filio", character: "Filio", spec: Some(Spec::Windwalker) },
+    Sim { member: "jakk", character: "Jakksparrow", spec: Some(Spec::Retribution) },
+    Sim { member: "dub", character: "Dubshamm", spec: None },
+    Sim { member: "hek", character: "Hekthuzad", spec: None },
+    Sim { member: "lan", character: "Lanathallan", spec: None },
+    Sim { member: "james", character: "Jaemsy", spec: None },
+    Sim { member: "ppd", character: "Ppdx", spec: None },
+    Sim { member: "vinnea", character: "Vinnea", spec: None },
+    Sim { member: "ladora", character: "Spyrodora", spec: None },
+    Sim { member: "kael", character: "Kaelirious", spec: None },
+    Sim { member: "nyans", character: "Nyanslok", spec: None },
+    // no sim currently
+    Sim { member: "cryptic", character: "Sodo", spec: None },
+];
 
 #[derive(Debug)]
-enum PlayerClass {
-    Warrior,
-    Mage,
-    Rogue,
-    Hunter,
-    Druid,
-    Paladin,
-    Priest,
-    Warlock,
-    Monk,
-    DeathKnight,
-    Shaman,
-    DemonHunter,
-    Evoker,
+pub(crate) struct Player {
+    /// the member's display name.
+    pub name: String,
+    /// the character that was simmed.
+    pub character: String,
+    pub class: PlayerClass,
+    /// the spec that was simmed, when we know it.
+    pub spec: Option<Spec>,
+    pub sim_url: String,
+}
+
+impl Player {
+    pub fn class_label(&self) -> String {
+        class_label(self.class, self.spec)
+    }
+
+    pub fn icon_url(&self) -> String {
+        match self.spec {
+            Some(spec) => spec.icon_url(),
+            None => self.class.icon_url(),
+        }
+    }
 }
 
-impl PlayerClass {
-    fn rgb(&self) -> &'static str {
-        match self {
-            PlayerClass::DeathKnight => "rgb(196, 30, 58)",
-            PlayerClass::DemonHunter => "rgb(163, 48, 201)",
-            PlayerClass::Druid => "rgb(255, 124, 10)",
-            PlayerClass::Evoker => "rgb(51, 147, 127)",
-            PlayerClass::Hunter => "rgb(170, 211, 114)",
-            PlayerClass::Mage => "rgb(63, 199, 235)",
-            PlayerClass::Monk => "rgb(0, 255, 152)",
-            PlayerClass::Paladin => "rgb(244, 140, 186)",
-            PlayerClass::Priest => "rgb(255, 255, 255)",
-            PlayerClass::Rogue => "rgb(255, 244, 104)",
-            PlayerClass::Shaman => "rgb(0, 112, 221)",
-            PlayerClass::Warlock => "rgb(135, 136, 238)",
-            PlayerClass::Warrior => "rgb(198, 155, 109)",
-       }
+// a sim whose member or character isn't in members.json is left off the page.
+fn sim_player(members: &MemberDocument, sim: &Sim) -> Option<Player> {
+    let member = members.member(sim.member);
+    let character = member.and_then(|member| member.character(sim.character));
+    match (member, character) {
+        (Some(member), Some(character)) => Some(Player {
+            name: member.display_name.clone(),
+            character: character.name.clone(),
+            class: character.class,
+            spec: sim.spec,
+            sim_url: format!("{}{}.html", SIM_URL, sim.character),
+        }),
+        _ => {
+            console_log!("No member {} with character {} for their sim", sim.member, sim.character);
+            None
+        }
     }
 }
-#[derive(Debug)]
-struct Player {
-    name: &'static str,
-    class: PlayerClass,
-    sim_url: &'static str,
+
+fn sim_players(members: &MemberDocument) -> Vec<Player> {
+    SIMS.iter().filter_map(|sim| sim_player(members, sim)).collect()
+}
+
+/// a member's sim, for their /player page.
+pub(crate) fn member_sim(members: &MemberDocument, member: &str) -> Option<Player> {
+    SIMS.iter()
+        .find(|sim| sim.member == member)
+        .and_then(|sim| sim_player(members, sim))
 }
 
 #[derive(Template)]
@@ -52,111 +104,36 @@ struct DamageSimsTemplate {
     players: Vec<Player>,
 }
 
-pub async fn damagesimspage() -> Html<String> {
-    let players = vec![
-        Player {
-            name: "Nuzz",
-            class: PlayerClass::Rogue,
-            sim_url: "https://r2.seemsgood.org/roster/Nuzzsin.html",
-        },
-        Player {
-            name: "Infi",
-            class: PlayerClass::Mage,
-            sim_url: "https://r2.seemsgood.org/roster/Infilicious.html",
-        },
-        Player {
-            name: "Shodo",
-            class: PlayerClass::Evoker,
-            sim_url: "https://r2.seemsgood.org/roster/Notshodo.html",
-        },
-        Player {
-            name: "Chint",
-            class: PlayerClass::DemonHunter,
-            sim_url: "https://r2.seemsgood.org/roster/Chinterfel.html",
-        },
-        Player {
-            name: "Roger",
-            class: PlayerClass::Druid,
-            sim_url: "https://r2.seemsgood.org/roster/Bigtittyrog.html",
-        },
-        Player {
-            name: "Chuubers",
-            class: PlayerClass::Warrior,
-            sim_url: "https://r2.seemsgood.org/roster/Chuubers.html",
-        },
-        Player {
-            name: "Delulu",
-            class: PlayerClass::Priest,
-            sim_url: "https://r2.seemsgood.org/roster/Delusionil.html",
-        },
-        Player {
-            name: "Filio",
-            class: PlayerClass::Monk,
-            sim_url: "https://r2.seemsgood.org/roster/Filio.html",
-        },
-        Player {
-            name: "Jakk",
-            class: PlayerClass::Paladin,
-            sim_url: "https://r2.seemsgood.org/roster/Jakksparrow.html",
-        },
-        Player {
-            name: "Dub",
-            class: PlayerClass::Shaman,
-            sim_url: "https://r2.seemsgood.org/roster/Dubshamm.html",
-        },
-        Player {
-            name: "Hek",
-            class: PlayerClass::Mage,
-            sim_url: "https://r2.seemsgood.org/roster/Hekthuzad.html",
-        },
-        Player {
-            name: "Lan",
-            class: PlayerClass::Warlock,
-            sim_url: "https://r2.seemsgood.org/roster/Lanathallan.html",
-        },
-        Player {
-            name: "James",
-            class: PlayerClass::Warrior,
-            sim_url: "https://r2.seemsgood.org/roster/Jaemsy.html",
-        },
-        Player {
-            name: "Ppd",
-            class: PlayerClass::Rogue,
-            sim_url: "https://r2.seemsgood.org/roster/Ppdx.html",
-        },
-        Player {
-            name: "Vinnea",
-            class: PlayerClass::Shaman,
-            sim_url: "https://r2.seemsgood.org/roster/Vinnea.html",
-        },
-        Player {
-            name: "Ladora",
-            class: PlayerClass::Evoker,
-            sim_url: "https://r2.seemsgood.org/roster/Spyrodora.html",
-        },
-        Player {
-            name: "Kael",
-            class: PlayerClass::Hunter,
-            sim_url: "https://r2.seemsgood.org/roster/Kaelirious.html",
-        },
-        Player {
-            name: "Nyans",
-            class: PlayerClass::Warlock,
-            sim_url: "https://r2.seemsgood.org/roster/Nyanslok.html",
-        },
-        // no sim currently, needed a deathknight to remove warning for being unused in enum
-        Player {
-            name: "Cryptic",
-            class: PlayerClass::DeathKnight,
-            sim_url: "https://r2.seemsgood.org/roster/Sodo.html",
-        },
-
-    ];
+#[worker::send]
+pub async fn damagesimspage(State(env): State<Env>) -> Response {
+    let members = match load_members(&Store::from_env(&env)).await {
+        Ok(members) => members,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, "Member list unavailable", &e.to_string()),
+    };
 
     let template = DamageSimsTemplate{ 
         base: BaseTemplate::new(true),
-        players 
+        players: sim_players(&members),
     };
     let rendered = template.render().unwrap();
-    Html(rendered)
+    Html(rendered).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_sim_resolves_against_the_bundled_members() {
+        let members = MemberDocument::from_json(include_str!("../templates/assets/members.json")).unwrap();
+
+        for sim in SIMS {
+            let player = sim_player(&members, sim)
+                .unwrap_or_else(|| panic!("{} has no character {} in members.json", sim.member, sim.character));
+            if let Some(spec) = sim.spec {
+                assert_eq!(spec.class(), player.class, "{}'s sim spec doesn't match their class", sim.character);
+            }
+        }
+        assert_eq!(sim_players(&members).len(), SIMS.len());
+    }
 }
@@ -0,0 +1,73 @@
+use worker::wasm_bindgen::JsValue;
+use worker::{Fetch, Headers, Method, Request, RequestInit};
+
+// +-------------+
+// | HTTP Client |
+// +-------------+
+// Outgoing requests (discord webhooks, wowaudit, etc.) go through HttpClient so they can be
+// pointed at a mock server instead of the real api.
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+pub trait HttpClient {
+    /// Err only when no response came back (dns, connection, etc.), any status is Ok.
+    /// errors leave the url out, webhook urls carry their token.
+    async fn post_json(&self, url: &str, body: &str) -> Result<HttpResponse, String>;
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, String>;
+}
+
+// the worker's fetch().
+#[derive(Default)]
+pub struct FetchClient;
+
+impl HttpClient for FetchClient {
+    async fn post_json(&self, url: &str, body: &str) -> Result<HttpResponse, String> {
+        let headers = Headers::new();
+        headers
+            .set("content-type", "application/json")
+            .map_err(|e| format!("Failed to build request: {:?}", e))?;
+        let mut init = RequestInit::new();
+        init.with_method(Method::Post)
+            .with_headers(headers)
+            .with_body(Some(JsValue::from_str(body)));
+        send(url, &init).await
+    }
+
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        let request_headers = Headers::new();
+        for (name, value) in headers {
+            request_headers
+                .set(name, value)
+                .map_err(|e| format!("Failed to build request: {:?}", e))?;
+        }
+        let mut init = RequestInit::new();
+        init.with_method(Method::Get).with_headers(request_headers);
+        send(url, &init).await
+    }
+}
+
+async fn send(url: &str, init: &RequestInit) -> Result<HttpResponse, String> {
+    let request = Request::new_with_init(url, init).map_err(|e| format!("Failed to build request: {:?}", e))?;
+    let mut response = Fetch::Request(request)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {:?}", e))?;
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response: {:?}", e))?;
+    Ok(HttpResponse {
+        status: response.status_code(),
+        body,
+    })
+}
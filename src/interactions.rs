@@ -0,0 +1,550 @@
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use worker::Env;
+
+use crate::cache::{LastGoodCache, WorkerCache};
+use crate::content::{load, lookup};
+use crate::player_metadata::{build_raid, build_roster, load_rosters, Player};
+use crate::progress::load_progress_card;
+use crate::season::{load_seasons, SeasonConfig};
+use crate::storage::{ContentStore, Store};
+use crate::wow::PlayerRole;
+use crate::wowaudit::load_events;
+
+// +----------------------+
+// | Discord Interactions |
+// +----------------------+
+// The worker answers slash commands at /interactions.
+// Discord signs every request with the application's Ed25519 key, unsigned requests get a 401.
+// Commands are registered with discord-commands.json (see README).
+
+/// wrangler secret, the application's public key from the Discord developer portal (hex).
+const PUBLIC_KEY: &str = "DISCORD_PUBLIC_KEY";
+const SIGNATURE_HEADER: &str = "x-signature-ed25519";
+const TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
+/// discord drops message content past this many characters.
+const MESSAGE_LIMIT: usize = 2000;
+/// message only the person who ran the command can see.
+const EPHEMERAL: u32 = 1 << 6;
+
+#[derive(Debug)]
+pub enum InteractionError {
+    MissingHeader(&'static str),
+    BadPublicKey,
+    BadSignature,
+    Malformed(String),
+}
+
+impl std::fmt::Display for InteractionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InteractionError::MissingHeader(name) => write!(f, "missing {} header", name),
+            InteractionError::BadPublicKey => write!(f, "{} is not a valid Ed25519 key", PUBLIC_KEY),
+            InteractionError::BadSignature => write!(f, "invalid request signature"),
+            InteractionError::Malformed(e) => write!(f, "malformed interaction: {}", e),
+        }
+    }
+}
+
+/// Check discord's signature over timestamp + body.
+pub fn verify_signature(
+    public_key_hex: &str,
+    signature_hex: &str,
+    timestamp: &str,
+    body: &[u8],
+) -> Result<(), InteractionError> {
+    let public_key: [u8; 32] = hex::decode(public_key_hex.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(InteractionError::BadPublicKey)?;
+    let public_key = VerifyingKey::from_bytes(&public_key).map_err(|_| InteractionError::BadPublicKey)?;
+    let signature: [u8; 64] = hex::decode(signature_hex.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(InteractionError::BadSignature)?;
+
+    let mut message = timestamp.as_bytes().to_vec();
+    message.extend_from_slice(body);
+    public_key
+        .verify(&message, &Signature::from_bytes(&signature))
+        .map_err(|_| InteractionError::BadSignature)
+}
+
+/// Check a request's signature headers against its body.
+pub fn verify_request(public_key_hex: &str, headers: &HeaderMap, body: &[u8]) -> Result<(), InteractionError> {
+    let header = |name: &'static str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(InteractionError::MissingHeader(name))
+    };
+    verify_signature(public_key_hex, header(SIGNATURE_HEADER)?, header(TIMESTAMP_HEADER)?, body)
+}
+
+// +-------------------+
+// | Interaction Types |
+// +-------------------+
+// https://discord.com/developers/docs/interactions/receiving-and-responding
+
+const PING: u8 = 1;
+const APPLICATION_COMMAND: u8 = 2;
+const PONG: u8 = 1;
+const CHANNEL_MESSAGE: u8 = 4;
+
+#[derive(Debug, Deserialize)]
+pub struct Interaction {
+    #[serde(rename = "type")]
+    pub kind: u8,
+    #[serde(default)]
+    pub data: Option<CommandData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandData {
+    pub name: String,
+    #[serde(default)]
+    pub options: Vec<CommandOption>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandOption {
+    pub name: String,
+    pub value: serde_json::Value,
+}
+
+impl CommandData {
+    /// a string option by name, blank values count as missing.
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|option| option.name == name)
+            .and_then(|option| option.value.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InteractionResponse {
+    #[serde(rename = "type")]
+    pub kind: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<MessageData>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MessageData {
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u32>,
+}
+
+impl InteractionResponse {
+    fn pong() -> Self {
+        Self { kind: PONG, data: None }
+    }
+
+    fn message(content: String) -> Self {
+        Self {
+            kind: CHANNEL_MESSAGE,
+            data: Some(MessageData {
+                content: truncate(content),
+                flags: None,
+            }),
+        }
+    }
+
+    /// only shown to the person who ran the command, used for errors.
+    fn ephemeral(content: String) -> Self {
+        Self {
+            kind: CHANNEL_MESSAGE,
+            data: Some(MessageData {
+                content: truncate(content),
+                flags: Some(EPHEMERAL),
+            }),
+        }
+    }
+}
+
+fn truncate(content: String) -> String {
+    if content.chars().count() <= MESSAGE_LIMIT {
+        return content;
+    }
+    let mut content: String = content.chars().take(MESSAGE_LIMIT - 1).collect();
+    content.push('…');
+    content
+}
+
+// +----------+
+// | Commands |
+// +----------+
+
+/// Answer one interaction. Only reads from the store, safe to run against a MemoryStore.
+pub async fn dispatch<S: ContentStore, C: LastGoodCache>(
+    store: &S,
+    last_good: &C,
+    interaction: &Interaction,
+) -> InteractionResponse {
+    if interaction.kind == PING {
+        return InteractionResponse::pong();
+    }
+    let Some(command) = interaction.data.as_ref().filter(|_| interaction.kind == APPLICATION_COMMAND) else {
+        return InteractionResponse::ephemeral("Unsupported interaction.".to_string());
+    };
+
+    let reply = match command.name.as_str() {
+        "roster" => roster_command(store, command.option("season")).await,
+        "progress" => progress_command(store, last_good).await,
+        "keys" => match command.option("character") {
+            Some(character) => keys_command(store, last_good, character).await,
+            None => Err("Usage: /keys <character>".to_string()),
+        },
+        "expectations" => expectations_command(store, command.option("section")).await,
+        other => Err(format!("Unknown command /{}.", other)),
+    };
+    match reply {
+        Ok(content) => InteractionResponse::message(content),
+        Err(e) => InteractionResponse::ephemeral(e),
+    }
+}
+
+// /roster [season], the roster for the season's newest kill (current season when left out).
+async fn roster_command<S: ContentStore>(store: &S, season: Option<&str>) -> Result<String, String> {
+    let seasons = load_seasons(store).await.map_err(|e| e.to_string())?;
+    let season_id = match season {
+        Some(season) => find_season(&seasons, season)?,
+        None => seasons.current_season.clone(),
+    };
+    let kills = build_raid(store, &seasons).await.map_err(|e| e.to_string())?;
+    let kill = kills
+        .iter()
+        .find(|kill| kill.season == season_id)
+        .ok_or_else(|| format!("No kills recorded for {}.", seasons.season_label(&season_id)))?;
+    let rosters = load_rosters(store).await.map_err(|e| e.to_string())?;
+    let players = build_roster(&rosters, &kill.fight_key).map_err(|e| e.to_string())?;
+
+    let mut content = format!(
+        "**{}** roster, {} ({})\n",
+        kill.fight_name,
+        seasons.season_label(&season_id),
+        kill.pretty_datetime()
+    );
+    for (role, heading) in [
+        (PlayerRole::Tank, "Tanks"),
+        (PlayerRole::Healer, "Healers"),
+        (PlayerRole::Dps, "Damage"),
+    ] {
+        let names: Vec<String> = players
+            .iter()
+            .filter(|player| player.role == role)
+            .map(roster_entry)
+            .collect();
+        if !names.is_empty() {
+            content.push_str(&format!("**{}:** {}\n", heading, names.join(", ")));
+        }
+    }
+    Ok(content)
+}
+
+fn roster_entry(player: &Player) -> String {
+    format!("{} ({})", player.name, player.class_label())
+}
+
+// season ids ("tww-s3") or names ("Season 3 of The War Within", "season 3").
+fn find_season(seasons: &SeasonConfig, query: &str) -> Result<String, String> {
+    let query = query.to_lowercase();
+    seasons
+        .seasons
+        .iter()
+        .rev()
+        .find(|season| {
+            season.id.to_lowercase() == query
+                || seasons.season_label(&season.id).to_lowercase().contains(&query)
+        })
+        .map(|season| season.id.clone())
+        .ok_or_else(|| {
+            let ids: Vec<&str> = seasons.seasons.iter().map(|season| season.id.as_str()).collect();
+            format!("Unknown season \"{}\", try one of: {}", query, ids.join(", "))
+        })
+}
+
+async fn progress_command<S: ContentStore, C: LastGoodCache>(store: &S, last_good: &C) -> Result<String, String> {
+    let seasons = load_seasons(store).await.map_err(|e| e.to_string())?;
+    let card = load_progress_card(store, last_good, &seasons).await;
+    let boss = card
+        .boss
+        .ok_or_else(|| format!("No progress recorded for {} yet.", card.raid_name))?;
+    let mut content = format!(
+        "**{}** ({})\nCurrent boss: **{}**, {} pulls, best pull {}",
+        card.raid_name, card.season_label, boss.name, boss.pull_count, boss.best_percent_for_display
+    );
+    if let Some(stale) = card.stale {
+        content.push_str(&format!("\n_last updated {}_", stale));
+    }
+    if let Some(url) = card.wcl_progress_url {
+        content.push_str(&format!("\n<{}>", url));
+    }
+    Ok(content)
+}
+
+// /keys <character>, this week's keys from wowaudit.
+async fn keys_command<S: ContentStore, C: LastGoodCache>(
+    store: &S,
+    last_good: &C,
+    character: &str,
+) -> Result<String, String> {
+    let seasons = load_seasons(store).await.map_err(|e| e.to_string())?;
+    let events = load_events(store, last_good).await.map_err(|e| e.to_string())?;
+    let found = events
+        .characters
+        .iter()
+        .find(|found| found.name.to_lowercase() == character.to_lowercase())
+        .ok_or_else(|| format!("{} isn't tracked in wowaudit.", character))?;
+    let Some(data) = &found.data else {
+        return Ok(format!("No data for {}-{} this week.", found.name, found.realm));
+    };
+    if data.dungeons_done.is_empty() {
+        return Ok(format!("{}-{} hasn't done a key this week.", found.name, found.realm));
+    }
+
+    let mut runs = data.dungeons_done.clone();
+    runs.sort_by_key(|run| std::cmp::Reverse(run.level));
+    let lines: Vec<String> = runs
+        .iter()
+        .map(|run| {
+            let dungeon = seasons
+                .dungeon(run.dungeon)
+                .map(|dungeon| dungeon.name.clone())
+                .unwrap_or_else(|| format!("Unknown ({})", run.dungeon));
+            format!("+{} {}", run.level, dungeon)
+        })
+        .collect();
+    Ok(format!(
+        "**{}-{}**, {} key(s) this week\n{}",
+        found.name,
+        found.realm,
+        runs.len(),
+        lines.join("\n")
+    ))
+}
+
+// /expectations [section], one section of the raider expectations, or the list of sections.
+async fn expectations_command<S: ContentStore>(store: &S, section: Option<&str>) -> Result<String, String> {
+    let entry = lookup("raider-expectations-source.md").ok_or("Raider expectations are not configured.")?;
+    let markdown = load(store, entry)
+        .await
+        .map_err(|_| "Raider expectations are unavailable right now.".to_string())?
+        .body;
+
+    let headings = markdown_headings(&markdown);
+    let Some(section) = section else {
+        let titles: Vec<String> = headings.iter().map(|(_, _, title)| format!("- {}", title)).collect();
+        return Ok(format!(
+            "**Raider Expectations**, pick a section with /expectations <section>:\n{}",
+            titles.join("\n")
+        ));
+    };
+
+    let query = section.to_lowercase();
+    let (index, (start, level, _)) = headings
+        .iter()
+        .enumerate()
+        .find(|(_, (_, _, title))| title.to_lowercase().contains(&query))
+        .ok_or_else(|| format!("No section matching \"{}\".", section))?;
+    // the section runs until the next heading at the same level or above.
+    let end = headings[index + 1..]
+        .iter()
+        .find(|(_, next_level, _)| next_level <= level)
+        .map(|(offset, _, _)| *offset)
+        .unwrap_or(markdown.len());
+    Ok(markdown[*start..end].trim().to_string())
+}
+
+// (byte offset, level, title) of every "# heading" line.
+fn markdown_headings(markdown: &str) -> Vec<(usize, usize, String)> {
+    let mut headings = Vec::new();
+    let mut offset = 0;
+    for line in markdown.split_inclusive('\n') {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            headings.push((offset, level, line[level..].trim().to_string()));
+        }
+        offset += line.len();
+    }
+    headings
+}
+
+// +---------+
+// | Handler |
+// +---------+
+
+#[worker::send]
+pub async fn interactions_handler(State(env): State<Env>, headers: HeaderMap, body: Bytes) -> Response {
+    let verified = env
+        .secret(PUBLIC_KEY)
+        .map_err(|_| InteractionError::BadPublicKey)
+        .and_then(|public_key| verify_request(&public_key.to_string(), &headers, &body));
+    if let Err(e) = verified {
+        console_log!("Rejected interaction: {}", e);
+        return (StatusCode::UNAUTHORIZED, e.to_string()).into_response();
+    }
+
+    let interaction: Interaction = match serde_json::from_slice(&body) {
+        Ok(interaction) => interaction,
+        Err(e) => {
+            let e = InteractionError::Malformed(e.to_string());
+            console_log!("{}", e);
+            return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+        }
+    };
+    let store = Store::from_env(&env);
+    Json(dispatch(&store, &WorkerCache::default(), &interaction).await).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStore, StoredObject};
+    use crate::testing::{block_on, MemoryCache};
+    use axum::http::HeaderValue;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    // a fixed keypair, so signatures are the same on every run.
+    const SECRET_KEY: [u8; 32] = [7; 32];
+    const TIMESTAMP: &str = "1760800000";
+    const BODY: &[u8] = br#"{"type":1}"#;
+
+    fn public_key_hex() -> String {
+        hex::encode(SigningKey::from_bytes(&SECRET_KEY).verifying_key().to_bytes())
+    }
+
+    fn sign(timestamp: &str, body: &[u8]) -> String {
+        let mut message = timestamp.as_bytes().to_vec();
+        message.extend_from_slice(body);
+        hex::encode(SigningKey::from_bytes(&SECRET_KEY).sign(&message).to_bytes())
+    }
+
+    fn headers(signature: Option<&str>, timestamp: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(signature) = signature {
+            headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(signature).unwrap());
+        }
+        if let Some(timestamp) = timestamp {
+            headers.insert(TIMESTAMP_HEADER, HeaderValue::from_str(timestamp).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn valid_signature() {
+        let signature = sign(TIMESTAMP, BODY);
+        let headers = headers(Some(&signature), Some(TIMESTAMP));
+
+        assert!(verify_request(&public_key_hex(), &headers, BODY).is_ok());
+    }
+
+    #[test]
+    fn tampered_body() {
+        let signature = sign(TIMESTAMP, BODY);
+        let headers = headers(Some(&signature), Some(TIMESTAMP));
+
+        let result = verify_request(&public_key_hex(), &headers, br#"{"type":2}"#);
+        assert!(matches!(result, Err(InteractionError::BadSignature)));
+    }
+
+    #[test]
+    fn bad_timestamp() {
+        let signature = sign(TIMESTAMP, BODY);
+        let headers = headers(Some(&signature), Some("1760800001"));
+
+        let result = verify_request(&public_key_hex(), &headers, BODY);
+        assert!(matches!(result, Err(InteractionError::BadSignature)));
+    }
+
+    #[test]
+    fn missing_header() {
+        let signature = sign(TIMESTAMP, BODY);
+
+        let result = verify_request(&public_key_hex(), &headers(Some(&signature), None), BODY);
+        assert!(matches!(result, Err(InteractionError::MissingHeader(TIMESTAMP_HEADER))));
+        let result = verify_request(&public_key_hex(), &headers(None, Some(TIMESTAMP)), BODY);
+        assert!(matches!(result, Err(InteractionError::MissingHeader(SIGNATURE_HEADER))));
+    }
+
+    #[test]
+    fn non_hex_signature() {
+        let headers = headers(Some("not-a-signature"), Some(TIMESTAMP));
+
+        let result = verify_request(&public_key_hex(), &headers, BODY);
+        assert!(matches!(result, Err(InteractionError::BadSignature)));
+    }
+
+    fn run(store: &MemoryStore, json: &str) -> InteractionResponse {
+        let interaction: Interaction = serde_json::from_str(json).unwrap();
+        block_on(dispatch(store, &MemoryCache::default(), &interaction))
+    }
+
+    fn content(response: &InteractionResponse) -> &str {
+        &response.data.as_ref().unwrap().content
+    }
+
+    fn is_ephemeral(response: &InteractionResponse) -> bool {
+        response.data.as_ref().is_some_and(|data| data.flags == Some(EPHEMERAL))
+    }
+
+    #[test]
+    fn ping_gets_pong() {
+        let response = run(&MemoryStore::new(), r#"{"type":1}"#);
+
+        assert_eq!(response.kind, PONG);
+        assert!(response.data.is_none());
+    }
+
+    // seasons, raids and rosters come from the bundled copies when the store is empty.
+    #[test]
+    fn roster_defaults_to_the_current_season() {
+        let response = run(&MemoryStore::new(), r#"{"type":2,"data":{"name":"roster"}}"#);
+
+        assert!(!is_ephemeral(&response));
+        assert!(content(&response).starts_with("**Dimensius** roster, Season 3 of The War Within"));
+        assert!(content(&response).contains("**Tanks:** Whare (Paladin), Purpformance (Monk)"));
+    }
+
+    #[test]
+    fn roster_for_a_season() {
+        let json = r#"{"type":2,"data":{"name":"roster","options":[{"name":"season","value":"tww-s2"}]}}"#;
+        let response = run(&MemoryStore::new(), json);
+
+        assert!(content(&response).starts_with("**Gallywix** roster, Season 2 of The War Within"));
+        assert!(content(&response).contains("Jaemsy (Warrior)"));
+    }
+
+    #[test]
+    fn expectations_unknown_section() {
+        let store = MemoryStore::new();
+        let markdown = "# Raider Expectations\n## Attendance\nShow up.\n## Loot\nBe nice.\n";
+        store.insert("content/raider-expectations.md", StoredObject::from_body(markdown));
+
+        let json = r#"{"type":2,"data":{"name":"expectations","options":[{"name":"section","value":"parking"}]}}"#;
+        let response = run(&store, json);
+        assert!(is_ephemeral(&response));
+        assert_eq!(content(&response), "No section matching \"parking\".");
+
+        let json = r#"{"type":2,"data":{"name":"expectations","options":[{"name":"section","value":"loot"}]}}"#;
+        assert_eq!(content(&run(&store, json)), "## Loot\nBe nice.");
+    }
+
+    #[test]
+    fn unknown_command() {
+        let response = run(&MemoryStore::new(), r#"{"type":2,"data":{"name":"dance"}}"#);
+
+        assert!(is_ephemeral(&response));
+        assert_eq!(content(&response), "Unknown command /dance.");
+    }
+}
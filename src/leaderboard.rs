@@ -0,0 +1,448 @@
+use std::cmp::Ordering;
+
+use askama_axum::Template;
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use serde::Deserialize;
+use worker::Env;
+
+use crate::cache::WorkerCache;
+use crate::http::FetchClient;
+use crate::mythic_plus::{character_keys, encode, players, popup_id, raiderio_api, CharacterKeys, KeysError, Player};
+use crate::player_metadata::load_members;
+use crate::season::{load_seasons, Dungeon};
+use crate::storage::Store;
+use crate::realm::same_character;
+use crate::time::Region;
+use crate::wow::{PlayerClass, PlayerRole};
+use crate::wowaudit::{is_stale, load_events_updated, HistoricalData, KeyTier};
+use crate::{error_page, BaseTemplate};
+
+// +---------------------+
+// | Mythic+ Leaderboard |
+// +---------------------+
+// /keys/leaderboard ranks the /keys players by raider.io score (through the /api/keys cache)
+// with this week's key count from wowaudit's events.json.
+
+/// raider.io lookups in flight at once when the cache is cold, one per /keys character otherwise.
+const RAIDERIO_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LeaderboardSort {
+    #[default]
+    Score,
+    Role,
+    Class,
+}
+
+impl std::str::FromStr for LeaderboardSort {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "score" => Ok(LeaderboardSort::Score),
+            "role" => Ok(LeaderboardSort::Role),
+            "class" => Ok(LeaderboardSort::Class),
+            _ => Err(format!("unknown sort: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    /// position after sorting, starts at 1. equal scores in the same group share a rank.
+    pub rank: usize,
+    pub name: String,
+    pub realm: String,
+    pub region: Region,
+    pub class: PlayerClass,
+    /// from the active spec on raider.io.
+    pub role: Option<PlayerRole>,
+    /// None when raider.io couldn't be reached for this character.
+    pub score: Option<f64>,
+    /// highest timed key per dungeon, in the season's dungeon order.
+    pub best_timed: Vec<Option<u32>>,
+    /// None when wowaudit has no data for the character, or none since the weekly reset.
+    pub runs_this_week: Option<usize>,
+}
+
+impl LeaderboardEntry {
+    /// link to the character's popup on /keys.
+    pub fn popup_link(&self) -> String {
+        format!("/keys#{}", encode(&popup_id(&self.name, &self.realm, self.region)))
+    }
+
+    /// the character's score over the season.
+    pub fn history_link(&self) -> String {
+        format!("/keys/history/{}", encode(&self.name))
+    }
+
+    pub fn realm_label(&self) -> String {
+        self.region.realm_label(&self.realm)
+    }
+
+    pub fn score_label(&self) -> String {
+        match self.score {
+            Some(score) => format!("{:.1}", score),
+            None => "-".to_string(),
+        }
+    }
+
+    pub fn role_label(&self) -> String {
+        match self.role {
+            Some(role) => role.to_string(),
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// events.json, unless it was written before this week's reset (wowaudit tracks the US week):
+/// last week's runs show as "-" until the refresh after reset, the same check as /vault.
+fn this_weeks_events(
+    events: HistoricalData,
+    updated_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<HistoricalData> {
+    if is_stale(updated_at, Region::Us.last_reset(now)) {
+        console_log!("Leaderboard without weekly runs: events.json is from before the reset");
+        return None;
+    }
+    Some(events)
+}
+
+/// Build one entry per player, in the player list's order. `profiles` lines up with `players`.
+pub fn build_entries(
+    players: &[Player],
+    profiles: &[Result<CharacterKeys, KeysError>],
+    dungeons: &[Dungeon],
+    events: Option<&HistoricalData>,
+) -> Vec<LeaderboardEntry> {
+    players
+        .iter()
+        .zip(profiles)
+        .map(|(player, profile)| {
+            let profile = profile.as_ref().ok();
+            let runs_this_week = events.and_then(|events| {
+                events
+                    .characters
+                    .iter()
+                    // wowaudit only tracks the guild's us characters.
+                    .filter(|_| player.region.is_us())
+                    .find(|character| same_character(&character.name, &character.realm, &player.name, &player.realm))
+                    .and_then(|character| character.data.as_ref())
+                    .map(|data| data.dungeons_done.len())
+            });
+            LeaderboardEntry {
+                rank: 0,
+                name: player.name.to_string(),
+                realm: player.realm.to_string(),
+                region: player.region,
+                class: player.class,
+                role: profile
+                    .and_then(|profile| profile.active_spec_role.as_deref())
+                    .and_then(|role| role.parse().ok()),
+                score: profile.map(|profile| profile.score),
+                best_timed: dungeons
+                    .iter()
+                    .map(|dungeon| profile.and_then(|profile| profile.best_timed(dungeon.id)))
+                    .collect(),
+                runs_this_week,
+            }
+        })
+        .collect()
+}
+
+/// Sort entries and number them. Ties (and every role or class group) go by score, then name.
+/// Characters without a score sort last. Equal scores share a rank (1, 2, 2, 4).
+pub fn rank(mut entries: Vec<LeaderboardEntry>, sort: LeaderboardSort) -> Vec<LeaderboardEntry> {
+    let group = |a: &LeaderboardEntry, b: &LeaderboardEntry| match sort {
+        LeaderboardSort::Score => Ordering::Equal,
+        LeaderboardSort::Role => role_order(a.role).cmp(&role_order(b.role)),
+        LeaderboardSort::Class => a.class.display_name().cmp(b.class.display_name()),
+    };
+    entries.sort_by(|a, b| {
+        group(a, b)
+            .then_with(|| compare_scores(a.score, b.score))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    for index in 0..entries.len() {
+        let tied = index > 0 && {
+            let (previous, entry) = (&entries[index - 1], &entries[index]);
+            group(previous, entry).then_with(|| compare_scores(previous.score, entry.score)).is_eq()
+        };
+        entries[index].rank = if tied { entries[index - 1].rank } else { index + 1 };
+    }
+    entries
+}
+
+// tanks, healers, dps, then anyone raider.io had no spec for.
+fn role_order(role: Option<PlayerRole>) -> u8 {
+    match role {
+        Some(PlayerRole::Tank) => 0,
+        Some(PlayerRole::Healer) => 1,
+        Some(PlayerRole::Dps) => 2,
+        None => 3,
+    }
+}
+
+// highest first, missing scores last.
+fn compare_scores(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[derive(Template)]
+#[template(path = "keys-leaderboard.html")]
+struct LeaderboardTemplate {
+    base: BaseTemplate,
+    sort: LeaderboardSort,
+    dungeons: Vec<Dungeon>,
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl LeaderboardTemplate {
+    fn key_color(&self, level: &u32) -> &'static str {
+        KeyTier::from_level(*level).color()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    sort: Option<String>,
+}
+
+/// GET /keys/leaderboard?sort=score|role|class
+#[worker::send]
+pub async fn leaderboard_page(State(env): State<Env>, Query(query): Query<LeaderboardQuery>) -> Response {
+    let sort = query
+        .sort
+        .as_deref()
+        .and_then(|sort| sort.parse().ok())
+        .unwrap_or_default();
+    let store = Store::from_env(&env);
+    let cache = WorkerCache::default();
+
+    let seasons = match load_seasons(&store).await {
+        Ok(seasons) => seasons,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Leaderboard unavailable", &e.to_string()),
+    };
+    let members = match load_members(&store).await {
+        Ok(members) => members,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Leaderboard unavailable", &e.to_string()),
+    };
+    let events = match load_events_updated(&store, &cache).await {
+        Ok((events, updated_at)) => this_weeks_events(events, updated_at, Utc::now()),
+        Err(e) => {
+            console_log!("Leaderboard without weekly runs: {}", e);
+            None
+        }
+    };
+
+    let api = raiderio_api(&env);
+    let players = players(&members);
+    // cached profiles come straight back, only cold ones wait on raider.io.
+    let profiles: Vec<Result<CharacterKeys, KeysError>> = stream::iter(
+        players
+            .iter()
+            .map(|player| character_keys(&FetchClient, &cache, &api, player.region, &player.realm, &player.name)),
+    )
+    .buffered(RAIDERIO_CONCURRENCY)
+    .map(|profile| profile.map(|response| response.keys))
+    .collect()
+    .await;
+    for (player, profile) in players.iter().zip(&profiles) {
+        if let Err(e) = profile {
+            console_log!("Leaderboard: {}-{}: {}", player.name, player.realm, e);
+        }
+    }
+
+    let dungeons = seasons.current().dungeons.clone();
+    let entries = rank(build_entries(&players, &profiles, &dungeons, events.as_ref()), sort);
+    let template = LeaderboardTemplate {
+        base: BaseTemplate::new(false),
+        sort,
+        dungeons,
+        entries,
+    };
+    let mut response = Html(template.render().unwrap()).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, "public, max-age=300".parse().unwrap());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const HISTORICAL_DATA: &str = include_str!("../domain/fixtures/wowaudit-historical-data.json");
+
+    fn entry(name: &str, class: PlayerClass, role: Option<PlayerRole>, score: Option<f64>) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank: 0,
+            name: name.to_string(),
+            realm: "Stormrage".to_string(),
+            region: Region::Us,
+            class,
+            role,
+            score,
+            best_timed: Vec::new(),
+            runs_this_week: None,
+        }
+    }
+
+    fn names(entries: &[LeaderboardEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    fn ranks(entries: &[LeaderboardEntry]) -> Vec<usize> {
+        entries.iter().map(|entry| entry.rank).collect()
+    }
+
+    #[test]
+    fn highest_score_first() {
+        let entries = rank(
+            vec![
+                entry("Nuzzler", PlayerClass::Druid, Some(PlayerRole::Dps), Some(2810.4)),
+                entry("Whare", PlayerClass::Paladin, Some(PlayerRole::Tank), Some(3120.0)),
+                entry("Piptide", PlayerClass::Shaman, Some(PlayerRole::Healer), Some(2950.7)),
+            ],
+            LeaderboardSort::Score,
+        );
+
+        assert_eq!(names(&entries), ["Whare", "Piptide", "Nuzzler"]);
+        assert_eq!(ranks(&entries), [1, 2, 3]);
+    }
+
+    #[test]
+    fn missing_scores_sort_last() {
+        let entries = rank(
+            vec![
+                entry("Ppddk", PlayerClass::DeathKnight, None, None),
+                entry("Nuzzler", PlayerClass::Druid, Some(PlayerRole::Dps), Some(0.0)),
+                entry("Aphitari", PlayerClass::Warrior, None, None),
+                entry("Whare", PlayerClass::Paladin, Some(PlayerRole::Tank), Some(3120.0)),
+            ],
+            LeaderboardSort::Score,
+        );
+
+        assert_eq!(names(&entries), ["Whare", "Nuzzler", "Aphitari", "Ppddk"]);
+    }
+
+    #[test]
+    fn role_groups_then_score() {
+        let entries = rank(
+            vec![
+                entry("Nuzzler", PlayerClass::Druid, Some(PlayerRole::Dps), Some(3000.0)),
+                entry("Unknown", PlayerClass::Mage, None, Some(3500.0)),
+                entry("Piptide", PlayerClass::Shaman, Some(PlayerRole::Healer), Some(2500.0)),
+                entry("Infilicious", PlayerClass::Mage, Some(PlayerRole::Dps), Some(3100.0)),
+                entry("Whare", PlayerClass::Paladin, Some(PlayerRole::Tank), Some(2000.0)),
+            ],
+            LeaderboardSort::Role,
+        );
+
+        assert_eq!(names(&entries), ["Whare", "Piptide", "Infilicious", "Nuzzler", "Unknown"]);
+        assert!(role_order(Some(PlayerRole::Tank)) < role_order(Some(PlayerRole::Healer)));
+        assert!(role_order(Some(PlayerRole::Healer)) < role_order(Some(PlayerRole::Dps)));
+        assert!(role_order(Some(PlayerRole::Dps)) < role_order(None));
+    }
+
+    #[test]
+    fn class_groups_then_score() {
+        let entries = rank(
+            vec![
+                entry("Rogerport", PlayerClass::Mage, Some(PlayerRole::Dps), Some(2900.0)),
+                entry("Nuzzler", PlayerClass::Druid, Some(PlayerRole::Dps), Some(2800.0)),
+                entry("Infilicious", PlayerClass::Mage, Some(PlayerRole::Dps), Some(3100.0)),
+            ],
+            LeaderboardSort::Class,
+        );
+
+        assert_eq!(names(&entries), ["Nuzzler", "Infilicious", "Rogerport"]);
+    }
+
+    #[test]
+    fn equal_scores_go_by_name_and_share_a_rank() {
+        let entries = rank(
+            vec![
+                entry("nyanslok", PlayerClass::Warlock, Some(PlayerRole::Dps), Some(2800.0)),
+                entry("Whare", PlayerClass::Paladin, Some(PlayerRole::Tank), Some(3000.0)),
+                entry("Indico", PlayerClass::Evoker, Some(PlayerRole::Dps), Some(2800.0)),
+                entry("Filio", PlayerClass::Monk, Some(PlayerRole::Dps), Some(2700.0)),
+            ],
+            LeaderboardSort::Score,
+        );
+
+        assert_eq!(names(&entries), ["Whare", "Indico", "nyanslok", "Filio"]);
+        assert_eq!(ranks(&entries), [1, 2, 2, 4]);
+    }
+
+    #[test]
+    fn equal_scores_in_different_groups_keep_their_own_rank() {
+        let entries = rank(
+            vec![
+                entry("Whare", PlayerClass::Paladin, Some(PlayerRole::Tank), Some(2800.0)),
+                entry("Piptide", PlayerClass::Shaman, Some(PlayerRole::Healer), Some(2800.0)),
+            ],
+            LeaderboardSort::Role,
+        );
+
+        assert_eq!(ranks(&entries), [1, 2]);
+    }
+
+    fn player(name: &str, realm: &str, region: Region) -> Player {
+        Player {
+            name: name.to_string(),
+            class: PlayerClass::Mage,
+            realm: realm.to_string(),
+            region,
+        }
+    }
+
+    fn runs(events: Option<&HistoricalData>) -> Vec<Option<usize>> {
+        let players = [
+            player("Fourkeys", "Area-52", Region::Us),
+            player("Zerorun", "Stormrage", Region::Us),
+            player("Unsynced", "Stormrage", Region::Us),
+            player("Fourkeys", "Area 52", Region::Eu),
+            player("Nobody", "Stormrage", Region::Us),
+        ];
+        let profiles: Vec<Result<CharacterKeys, KeysError>> =
+            players.iter().map(|_| Err(KeysError::Upstream("offline".to_string()))).collect();
+        build_entries(&players, &profiles, &[], events)
+            .into_iter()
+            .map(|entry| entry.runs_this_week)
+            .collect()
+    }
+
+    #[test]
+    fn runs_this_week_from_events() {
+        let events = HistoricalData::from_json(HISTORICAL_DATA).unwrap();
+
+        assert_eq!(runs(Some(&events)), [Some(4), Some(0), None, None, None]);
+        assert_eq!(runs(None), [None; 5]);
+    }
+
+    #[test]
+    fn runs_from_before_the_reset_are_left_out() {
+        let events = || HistoricalData::from_json(HISTORICAL_DATA).unwrap();
+        // Wednesday 2025-10-15, the week started Tuesday 15:00 UTC.
+        let now = Utc.with_ymd_and_hms(2025, 10, 15, 12, 0, 0).unwrap();
+
+        let fresh = this_weeks_events(events(), Some(Utc.with_ymd_and_hms(2025, 10, 15, 11, 30, 0).unwrap()), now);
+        assert_eq!(runs(fresh.as_ref())[0], Some(4));
+        let stale = this_weeks_events(events(), Some(Utc.with_ymd_and_hms(2025, 10, 14, 14, 30, 0).unwrap()), now);
+        assert!(stale.is_none());
+        // the bundled copy has no upload time.
+        assert!(this_weeks_events(events(), None, now).is_none());
+    }
+}
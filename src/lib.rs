@@ -1,5 +1,6 @@
-use axum::{routing::get, Router, response::Redirect};
+use axum::{routing::{get, post}, Router, response::Redirect};
 use axum::response::IntoResponse;
+use axum::extract::State;
 use axum::http::{StatusCode, header};
 use tower_service::Service;
 use worker::*;
@@ -7,24 +8,70 @@ use axum::response::Html;
 use include_dir::{include_dir, Dir};
 use askama_axum::Template;
 use std::collections::HashMap;
-use comrak::{markdown_to_html, ComrakOptions};
+use comrak::ComrakOptions;
 
 
+// classes, specs and roles, shared with sgctl.
+use seemsgood_domain::wow;
+// weekly resets, raid nights and timezones.
+use seemsgood_domain::time;
+// realm catalogue, slugs and outbound character links.
+use seemsgood_domain::realm;
+// guild members, their characters and ranks.
+use seemsgood_domain::member;
+// joins, departures and swaps between season rosters.
+use seemsgood_domain::roster_diff;
+use chrono::Datelike;
+
 // +-------------+
 // | Git Version |
 // +-------------+
 const GIT_HASH: &str = env!("GIT_HASH");
 const GIT_BRANCH: &str = env!("GIT_BRANCH");
-const CURRENT_YEAR: &str = env!("CURRENT_YEAR");
-
+/// year the seemsgood_guild repo was created with its license.
+const FIRST_YEAR: i32 = 2024;
+
+
+// +---------+
+// | Logging |
+// +---------+
+// worker::console_log! writes to the Workers console, which only exists on wasm.
+// Native builds (cargo test) print to stderr instead. Defined before the modules so they all see it.
+macro_rules! console_log {
+    ($($arg:tt)*) => {{
+        #[cfg(target_arch = "wasm32")]
+        worker::console_log!($($arg)*);
+        #[cfg(not(target_arch = "wasm32"))]
+        eprintln!($($arg)*);
+    }};
+}
 
 // +----------------+
 // | Template logic |
 // +----------------+
 mod dps_sims; 
 mod mythic_plus;
+mod leaderboard;
+mod score_history;
+mod vault;
+mod profile;
+mod rosters;
+mod realms;
 mod player_metadata;
 mod about_data;
+mod content;
+mod storage;
+mod cache;
+mod wowaudit;
+mod progress;
+mod season;
+mod http;
+mod discord;
+mod application;
+mod interactions;
+mod refresh;
+#[cfg(test)]
+mod testing;
 
 // +---------------+
 // | Static Assets |
@@ -32,25 +79,36 @@ mod about_data;
 
 // Include html, css, and and media in local repo.
 static ASSETS_DIR: Dir = include_dir!("templates");
-// R2 Endpoints for dynamic content
-// TODO: make generic handler for /content/
-const EVENTS_JSON_URL: &str = "https://r2.seemsgood.org/content/events.json";
-const PROGRESS_JSON_URL: &str = "https://r2.seemsgood.org/content/progress.json";
-const RAIDER_EXPECTATIONS_URL: &str = "https://markdown-r2.seemsgood.org/raider-expectations.md";
-
-// All routes for webpage that are not dynamic.
-fn router() -> Router {
+// R2 documents are served from /content/{name}, see content.rs for the allowed list.
+
+// All routes for webpage.
+fn router(env: Env) -> Router {
     Router::new() 
         .route("/", get(home_page))
         .route("/about", get(about_page))
-        .route("/application", get(apply_page))
+        .route("/application", get(application::apply_page).post(application::submit_handler))
+        .route("/applications", get(application::applications_page))
         .route("/dps-sims", get(dps_sims::damagesimspage))
         .route("/keys",  get(mythic_plus::mythicplus_page))
+        .route("/keys/leaderboard", get(leaderboard::leaderboard_page))
+        .route("/keys/history/:name", get(score_history::history_page))
+        .route("/player/:region/:realm/:name", get(profile::profile_page))
+        .route("/rosters/diff", get(rosters::roster_diff_page))
+        .route("/api/rosters/diff", get(rosters::roster_diff_api_handler))
+        .route("/api/keys/:region/:realm/:name", get(mythic_plus::keys_api_handler))
+        .route("/api/realms", get(realms::realms_api_handler))
         .route("/wowaudit", get(wowaudit_page))
+        .route("/vault", get(vault::vault_page))
         .route("/talents", get(talents_page))
         .route("/resources", get(resources_page))
         .route("/css/bulma.min.css", get(bulma_css_handler))
+        .route("/content/:name", get(content::content_handler))
+        .route("/events", get(content::events_handler))
+        .route("/progress", get(content::progress_handler))
+        .route("/expectations", get(content::expectations_handler))
+        .route("/interactions", post(interactions::interactions_handler))
         .fallback(Redirect::permanent("/"))
+        .with_state(env)
 }
 
 // +-------------------+
@@ -60,27 +118,37 @@ fn router() -> Router {
 #[event(fetch)]
 async fn fetch(
     req: HttpRequest,
-    _env: Env,
+    env: Env,
     _ctx: Context,
 ) -> Result<axum::http::Response<axum::body::Body>> {
     console_error_panic_hook::set_once();
-    
-    // Handle /events and /progress routes manually before passing to router
-    let path = req.uri().path();
-   
-    if path == "/progress" {
-        return Ok(fetch_json_endpoint(PROGRESS_JSON_URL, "assets/progress.json").await);
-    }
-    if path == "/events" {
-        return Ok(fetch_json_endpoint(EVENTS_JSON_URL, "assets/events.json").await);
-    }
-    // Handle /expectations (gh url)
-    if path == "/expectations" {
-        return Ok(fetch_html_endpoint(RAIDER_EXPECTATIONS_URL, "assets/404.html").await);
+    Ok(router(env).call(req).await?)
+}
+
+// cron triggers (see wrangler.toml): daily M+ score snapshots, otherwise refresh wowaudit documents in R2.
+#[event(scheduled)]
+async fn scheduled(event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
+    console_error_panic_hook::set_once();
+    let store = storage::Store::from_env(&env);
+    if event.cron() == score_history::SNAPSHOT_CRON {
+        console_log!("Cron {} recording M+ score snapshots", event.cron());
+        let api = mythic_plus::raiderio_api(&env);
+        let today = chrono::Utc::now().date_naive();
+        if let Err(e) = score_history::record_snapshots(&store, &http::FetchClient, &api, today).await {
+            console_log!("Score snapshots failed: {}", e);
+        }
+        return;
     }
 
-    // For all other routes, use the router
-    Ok(router().call(req).await?)
+    let token = match env.secret(refresh::WOWAUDIT_TOKEN) {
+        Ok(token) => token.to_string(),
+        Err(e) => {
+            console_log!("{} is not set, skipping refresh: {:?}", refresh::WOWAUDIT_TOKEN, e);
+            return;
+        }
+    };
+    console_log!("Cron {} refreshing wowaudit data", event.cron());
+    refresh::refresh_wowaudit(&store, &http::FetchClient, &token).await;
 }
 
 // +----------------------------+
@@ -88,6 +156,7 @@ async fn fetch(
 // +----------------------------+
 
 /// use in functions that call ComrakOptions::default()
+/// (see content.rs for markdown documents)
 /// keeps extensions same, less repeated code.
 /// example:
 /// ```rust
@@ -107,74 +176,9 @@ fn enable_extensions(options: &mut ComrakOptions) {
     options.extension.spoiler = true;
 }
 
-// +------------------------------+
-// | Handlers for Dynamic Content |
-// +------------------------------+
-
-// Fetch HTML from $url. Fallback on local file. 
-// Request  | /expectations -> fetch_html_endpoint -> |MARKDOWN.md| 
-// Response | /expectations <- markdown_to_html    <- |MARKDOWN.md|
-async fn fetch_html_endpoint(url: &str, fallback_path: &str) -> axum::http::Response<axum::body::Body> {
-
-    let html_data = match fetch_html_from_whitelist(url).await {
-        Ok(data) => data,
-        Err(e) => {
-            eprintln!("Error fetching HTML from {}: {}", url, e);
-            match ASSETS_DIR.get_file(fallback_path) {
-                Some(file) => file.contents_utf8().unwrap_or("").to_string(),
-                None => "<p>Content unavailable.</p>".to_string(),
-            }
-        }
-    };
-    let mut options = ComrakOptions::default(); 
-    enable_extensions(&mut options);
-    let markdown = format!(
-        r#"<div class="markdown-body">{}</div>"#,
-        markdown_to_html(&html_data, &options)
-    );
-
-    (
-        [
-        (header::CONTENT_TYPE, "text/html; charset=utf-8"),
-        (header::CACHE_CONTROL, "no-cache, no-store, must-revalidate"),
-        ],
-        markdown,
-    )
-        .into_response()
-}
-
-// Protect against arbitrary HTML only allow scoped URLs
-async fn fetch_html_from_whitelist(url: &str) -> Result<String, String> {
-    if url != RAIDER_EXPECTATIONS_URL {
-        console_log!(
-            "SECURITY: Blocked attempt to fetch non-whitelisted HTML URL: {}",
-            url
-        );
-        return Err("URL not whitelisted".into());
-    }
-
-    let mut request_init = RequestInit::new();
-    request_init.with_method(Method::Get);
-
-    let request = Request::new_with_init(url, &request_init)
-        .map_err(|e| format!("Failed to create request: {:?}", e))?;
-
-    let mut response = Fetch::Request(request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch HTML: {:?}", e))?;
-
-    let status = response.status_code();
-    if status < 200 || status >= 300 {
-        return Err(format!("HTML request failed with status: {}", status));
-    }
-
-    response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read HTML response: {:?}", e))
-}
-
+// +-----------------+
+// | Static Handlers |
+// +-----------------+
 
 // Handler for ../templates/css/bulma.min.css 
 async fn bulma_css_handler() -> axum::http::Response<axum::body::Body> {
@@ -193,61 +197,6 @@ async fn bulma_css_handler() -> axum::http::Response<axum::body::Body> {
     }
 }
 
-// Generic to fetch JSON from endpoint with fallback
-async fn fetch_json_endpoint(url: &str, fallback_path: &str) -> axum::http::Response<axum::body::Body> {
-    // Try to fetch from API
-    let json_data = match fetch_from_r2(url).await {
-        Ok(data) => data,
-        Err(e) => {
-            eprintln!("Error fetching from {}: {}", url, e);
-            // Fallback to static file
-            match ASSETS_DIR.get_file(fallback_path) {
-                Some(file) => file.contents_utf8().unwrap_or("{}").to_string(),
-                None => "{}".to_string(),
-            }
-        }
-    };
-    
-    (
-        [
-            (header::CONTENT_TYPE, "application/json"),
-            (header::CACHE_CONTROL, "no-cache, no-store, must-revalidate"),
-        ],
-        json_data
-    ).into_response()
-}
-
-// Helper to fetch JSON from R2
-async fn fetch_from_r2(url: &str) -> std::result::Result<String, String> {
-    // Verify input of url here. 
-    if url != EVENTS_JSON_URL && url != PROGRESS_JSON_URL {
-        console_log!("SECURITY: Blocked attempt to fetch from non-whitelisted URL: {}", url);
-        return Err(format!("URL not whitelisted: {}", url));
-    }
-    
-    let mut request_init = RequestInit::new();
-    request_init.with_method(Method::Get);
-    
-    let request = Request::new_with_init(url, &request_init)
-        .map_err(|e| format!("Failed to create request: {:?}", e))?;
-    
-    let mut response = Fetch::Request(request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch: {:?}", e))?;
-    
-    let status = response.status_code();
-    if status < 200 || status >= 300 {
-        return Err(format!("Request failed with status: {}", status));
-    }
-    
-    response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response text: {:?}", e))
-}
-
-
 // +---------------+
 // | Base Template |
 // +---------------+
@@ -268,17 +217,16 @@ impl GitInfo {
     }
 }
 
-// fix static typed date in layout.html 
-// get current year at build time.
+// copyright years in layout.html, "2024-{this year}" from the request's clock (Date.now()).
 #[derive(Clone)]
 pub struct DateInfo {
-    pub date: &'static str,
+    pub date: String,
 }
 
 impl DateInfo {
     pub fn current() -> Self {
         Self {
-            date: CURRENT_YEAR,
+            date: format!("{}-{}", FIRST_YEAR, chrono::Utc::now().year()),
         }
     }
 }
@@ -301,50 +249,120 @@ impl BaseTemplate {
 }
 
 
+// Error Page
+// Shown when a page can't be built from its data (ex: unknown roster id).
+#[derive(Template)]
+#[template(path = "error.html")]
+struct ErrorTemplate<'a> {
+    base: BaseTemplate,
+    status: u16,
+    title: &'a str,
+    message: &'a str,
+}
+pub(crate) fn error_page(status: StatusCode, title: &str, message: &str) -> axum::response::Response {
+    console_log!("{} {}: {}", status.as_u16(), title, message);
+    let template = ErrorTemplate {
+        base: BaseTemplate::new(false),
+        status: status.as_u16(),
+        title,
+        message,
+    };
+    let rendered = template.render().unwrap();
+    (status, Html(rendered)).into_response()
+}
+
 
 // +---------------------------+
 // | Build Pages from Templates|
 // +---------------------------+
 
 // Home Page
-use player_metadata::{build_roster, Player, build_raid, RaidMetaData};
+use player_metadata::{build_roster, load_members, load_rosters, MemberDocument, Player, build_raid, RaidMetaData};
+use storage::Store;
+use cache::WorkerCache;
+use wowaudit::{load_events, MarqueeLine};
+use progress::{load_progress_card, ProgressCard};
+use season::{load_seasons, SeasonConfig};
 #[derive(Template)]
 #[template(path = "index.html")]
 struct IndexTemplate {
     base: BaseTemplate,
+    seasons: SeasonConfig,
     raid_metadata: Vec<RaidMetaData>,
     rosters: HashMap<String, Vec<Player>>, 
+    members: MemberDocument,
+    progress: ProgressCard,
+    marquee: Vec<MarqueeLine>,
+    events_error: Option<String>,
 }
-async fn home_page() -> Html<String> {
+
+impl IndexTemplate {
+    /// who plays a roster character, when the guild knows them by another name.
+    fn member_name(&self, player: &Player) -> Option<String> {
+        self.members
+            .for_player(player)
+            .map(|member| member.display_name.clone())
+            .filter(|name| !name.eq_ignore_ascii_case(&player.name))
+    }
+}
+
+#[worker::send]
+async fn home_page(State(env): State<Env>) -> axum::response::Response {
+    let store = Store::from_env(&env);
+    let seasons = match load_seasons(&store).await {
+        Ok(seasons) => seasons,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, "Season data unavailable", &e.to_string()),
+    };
+    let doc = match load_rosters(&store).await {
+        Ok(doc) => doc,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, "Rosters unavailable", &e.to_string()),
+    };
+    let members = match load_members(&store).await.and_then(|members| members.check_roster(&doc).map(|_| members)) {
+        Ok(members) => members,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, "Member list unavailable", &e.to_string()),
+    };
+    let mut raid_metadata = match build_raid(&store, &seasons).await {
+        Ok(kills) => kills,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, "Raid history unavailable", &e.to_string()),
+    };
+
+    // every kill card needs a roster for its popup. `sgctl content push` refuses a kill without
+    // one, if one gets through anyway it is left off rather than taking the page down.
     let mut rosters = HashMap::new();
-    rosters.insert("Dimensius".to_string(), build_roster("Dimensius"));
-    rosters.insert("Gallywix".to_string(), build_roster("Gallywix"));
-    rosters.insert("Kyvesa".to_string(), build_roster("Kyvesa"));
-    rosters.insert("Fyrakk".to_string(), build_roster("Fyrakk"));
+    raid_metadata.retain(|card| match build_roster(&doc, &card.fight_key) {
+        Ok(players) => {
+            rosters.insert(card.fight_key.clone(), players);
+            true
+        }
+        Err(e) => {
+            console_log!("Leaving {} off the home page: {}", card.fight_name, e);
+            false
+        }
+    });
+
+    // progress and recent keys are nice to have, the page still renders without them.
+    let last_good = WorkerCache::default();
+    let progress = load_progress_card(&store, &last_good, &seasons).await;
+    let (marquee, events_error) = match load_events(&store, &last_good).await {
+        Ok(events) => (events.marquee_lines(&seasons), None),
+        Err(e) => {
+            console_log!("{}", e);
+            (Vec::new(), Some(e.to_string()))
+        }
+    };
 
-    let raid_metadata = build_raid();
     let template = IndexTemplate { 
         base: BaseTemplate::new(true),
+        seasons,
         raid_metadata,
         rosters,
+        members,
+        progress,
+        marquee,
+        events_error,
     };
     let rendered = template.render().unwrap();
-    Html(rendered)
-}
-
-// Apply Page 
-#[derive(Template)]
-#[template(path = "apply.html")]
-struct ApplyTemplate {
-    base: BaseTemplate,
-
-}
-async fn apply_page() -> Html<String> {
-    let template = ApplyTemplate { 
-        base: BaseTemplate::new(false)
-    };
-    let rendered = template.render().unwrap();
-    Html(rendered)
+    Html(rendered).into_response()
 }
 
 // About Page
@@ -354,15 +372,30 @@ use about_data::{ContactInfo, build_contacts};
 struct AboutTemplate {
     base: BaseTemplate,
     contacts: Vec<ContactInfo>,
+    /// "Tuesdays and Thursdays from 8:00 PM - 11:00 PM EST"
+    schedule: String,
+    /// "Tuesday 14 October, 8:00 PM EDT", None when there are no raid nights.
+    next_raid: Option<String>,
 }
-async fn about_page() -> Html<String> {
-    let contacts = build_contacts();
+#[worker::send]
+async fn about_page(State(env): State<Env>) -> axum::response::Response {
+    let members = match load_members(&Store::from_env(&env)).await {
+        Ok(members) => members,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, "Member list unavailable", &e.to_string()),
+    };
+    let contacts = build_contacts(&members);
+    let now = chrono::Utc::now();
+    let schedule = time::RaidSchedule::guild();
     let template = AboutTemplate { 
         base: BaseTemplate::new(true),
         contacts,
+        schedule: schedule.label(now),
+        next_raid: schedule
+            .next_raid(now)
+            .map(|(start, _)| schedule.zone.format(start, "%A %-d %B, %-I:%M %p %Z")),
     };
     let rendered = template.render().unwrap();
-    Html(rendered)
+    Html(rendered).into_response()
 }
 
 // Spreadsheet Page (wowaudit)
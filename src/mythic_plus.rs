@@ -1,48 +1,26 @@
 use askama_axum::Template;
-use crate::BaseTemplate;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use worker::Env;
+use crate::{error_page, BaseTemplate};
+use crate::cache::{LastGoodCache, WorkerCache};
+use crate::http::{FetchClient, HttpClient};
+use crate::player_metadata::{load_members, MemberDocument};
+use crate::realm::realm_slug;
+use crate::storage::Store;
+use crate::time::Region;
+use crate::wow::PlayerClass;
 
 #[derive(Debug)]
-enum PlayerClass {
-    Warrior,
-    Mage,
-    Rogue,
-    Hunter,
-    Druid,
-    Paladin,
-    Priest,
-    Warlock,
-    Monk,
-    DeathKnight,
-    Shaman,
-    DemonHunter,
-    Evoker,
-}
-
-impl PlayerClass {
-    fn rgb(&self) -> &'static str {
-        match self {
-            PlayerClass::DeathKnight => "rgb(196, 30, 58)",
-            PlayerClass::DemonHunter => "rgb(163, 48, 201)",
-            PlayerClass::Druid => "rgb(255, 124, 10)",
-            PlayerClass::Evoker => "rgb(51, 147, 127)",
-            PlayerClass::Hunter => "rgb(170, 211, 114)",
-            PlayerClass::Mage => "rgb(63, 199, 235)",
-            PlayerClass::Monk => "rgb(0, 255, 152)",
-            PlayerClass::Paladin => "rgb(244, 140, 186)",
-            PlayerClass::Priest => "rgb(255, 255, 255)",
-            PlayerClass::Rogue => "rgb(255, 244, 104)",
-            PlayerClass::Shaman => "rgb(0, 112, 221)",
-            PlayerClass::Warlock => "rgb(135, 136, 238)",
-            PlayerClass::Warrior => "rgb(198, 155, 109)",
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Player {
-    name: &'static str,
-    class: PlayerClass,
-    realm: &'static str,
+pub(crate) struct Player {
+    pub name: String,
+    pub class: PlayerClass,
+    pub realm: String,
+    pub region: Region,
 }
 
 #[derive(Template)]
@@ -50,72 +28,484 @@ struct Player {
 struct RaidFramesTemplate {
     base: BaseTemplate,
     players: Vec<Player>,
+    /// for the add character form's class buttons.
+    classes: [PlayerClass; 13],
+    /// for the add character form's region select.
+    regions: [Region; 2],
 }
 
 
-pub async fn mythicplus_page() -> axum::response::Html<String> {
-    // Creating the list of players, including class RGB
-    let players = vec![
-        Player { name: "Aphitari", class: PlayerClass::Warrior, realm: "Area 52" },
-        Player { name: "Philfishh", class: PlayerClass::Monk, realm: "Area 52" },
-        Player { name: "Ovtlaw", class: PlayerClass::Rogue, realm: "Dalaran" },
-        Player { name: "Oldmanzand", class: PlayerClass::Shaman, realm: "Illidan" },
-        Player { name: "Ppddk", class: PlayerClass::DeathKnight, realm: "Mal'Ganis" },
-        Player { name: "Delusionol", class: PlayerClass::Priest, realm: "Stormrage" },
-        Player { name: "Evelianne", class: PlayerClass::Monk, realm: "Stormrage" },
-        Player { name: "Fliptwisty", class: PlayerClass::DemonHunter, realm: "Stormrage" },
-        Player { name: "Infilicious", class: PlayerClass::Mage, realm: "Stormrage" },
-        Player { name: "Notshodo", class: PlayerClass::Evoker, realm: "Stormrage" },
-        Player { name: "Nuzzler", class: PlayerClass::Druid, realm: "Stormrage" },
-        Player { name: "Nyanslok", class: PlayerClass::Warlock, realm: "Stormrage" },
-        Player { name: "Obiscuit", class: PlayerClass::DeathKnight, realm: "Stormrage" },
-        Player { name: "Ppdx", class: PlayerClass::Rogue, realm: "Stormrage" },
-        Player { name: "Purpleheal", class: PlayerClass::Priest, realm: "Stormrage" },
-        Player { name: "Rogerport", class: PlayerClass::Mage, realm: "Stormrage" },
-        Player { name: "Whare", class: PlayerClass::Paladin, realm: "Stormrage" },
-        Player { name: "Prankdaddy", class: PlayerClass::Evoker, realm: "Thrall" },
-        Player { name: "Rektribute", class: PlayerClass::Paladin, realm: "Thrall" },
-        Player { name: "Stormbreed", class: PlayerClass::Hunter, realm: "Thrall" },
-        Player { name: "Piptide", class: PlayerClass::Shaman, realm: "Tichondrius" },
-        Player { name: "Kolzane", class: PlayerClass::Hunter, realm: "Ysera" },
-        Player { name: "Indico", class: PlayerClass::Evoker, realm: "Zul'jin" },
-
-        // // tanks
-        // Player { name: "Jtusendh", class: PlayerClass::DemonHunter, realm: "Stormrage" },
-        // Player { name: "Paliduh", class: PlayerClass::Paladin, realm: "Stormrage" },
-        // // healers
-        // Player { name: "Delusionol", class: PlayerClass::Priest, realm: "Stormrage" },
-        // Player { name: "Evelianne", class: PlayerClass::Monk, realm: "Stormrage" },
-        // Player { name: "Pipmeow", class: PlayerClass::Druid, realm: "Tichondrius" },
-        // Player { name: "Auraelia", class: PlayerClass::Priest, realm: "Zul'jin" },
-        // Player { name: "Caael", class: PlayerClass::Paladin, realm: "Zul'jin" },
-        // // damage 
-        // Player { name: "Infilicious", class: PlayerClass::Mage, realm: "Stormrage" },
-        // Player { name: "Hekthuzad", class: PlayerClass::Mage, realm: "Stormrage" },
-        // Player { name: "Indico", class: PlayerClass::Evoker, realm: "Zul'jin" },
-        // Player { name: "Ladöra", class: PlayerClass::Priest, realm: "Stormrage" },
-        // Player { name: "Lanathallan", class: PlayerClass::Warlock, realm: "Stormrage" },
-        // Player { name: "Notshodo", class: PlayerClass::Evoker, realm: "Stormrage" },
-        // Player { name: "Kolzane", class: PlayerClass::Hunter, realm: "Ysera" },
-        // Player { name: "Speara", class: PlayerClass::Druid, realm: "Kel'Thuzad" },
-        // Player { name: "Nyanslok", class: PlayerClass::Warlock, realm: "Stormrage" },
-        // Player { name: "Dubshamm", class: PlayerClass::Shaman, realm: "Stormrage" },
-        // Player { name: "Chuubers", class: PlayerClass::Warrior, realm: "Stormrage" },
-        // Player { name: "Conncrete", class: PlayerClass::Rogue, realm: "Tichondrius" },
-        // Player { name: "Filio", class: PlayerClass::Monk, realm: "Stormrage" },
-        // Player { name: "Jakksparrow", class: PlayerClass::Paladin, realm: "Stormrage" },
-        // Player { name: "Juukmonk", class: PlayerClass::Monk, realm: "Zul'jin" },
-        // Player { name: "Ppdx", class: PlayerClass::Rogue, realm: "Stormrage" },
-        // Player { name: "Queldk", class: PlayerClass::DeathKnight, realm: "Zul'jin" },
-    ];
+impl Player {
+    /// data-popup of the player's raid frame, /keys#{popup} opens their popup.
+    pub fn popup_id(&self) -> String {
+        popup_id(&self.name, &self.realm, self.region)
+    }
+}
 
+/// "whare-stormrage", other regions get a suffix ("whare-stormrage-eu") so us ids stay as they
+/// were (score history is keyed by them).
+pub(crate) fn popup_id(name: &str, realm: &str, region: Region) -> String {
+    match region {
+        Region::Us => format!("{}-{}", name.to_lowercase(), realm_slug(realm)),
+        region => format!("{}-{}-{}", name.to_lowercase(), realm_slug(realm), region),
+    }
+}
 
+/// The players on /keys (characters with "keys": true in members.json), also ranked on
+/// /keys/leaderboard. Sorted by realm, then name.
+pub(crate) fn players(members: &MemberDocument) -> Vec<Player> {
+    let mut players: Vec<Player> = members
+        .keys_characters()
+        .map(|(_, character)| Player {
+            name: character.name.clone(),
+            class: character.class,
+            realm: character.realm.clone(),
+            region: character.region,
+        })
+        .collect();
+    players.sort_by(|a, b| a.realm.cmp(&b.realm).then_with(|| a.name.cmp(&b.name)));
+    players
+}
+
+#[worker::send]
+pub async fn mythicplus_page(State(env): State<Env>) -> Response {
+    let members = match load_members(&Store::from_env(&env)).await {
+        Ok(members) => members,
+        Err(e) => return error_page(StatusCode::INTERNAL_SERVER_ERROR, "Member list unavailable", &e.to_string()),
+    };
 
     // Rendering the template with the player data
     let template = RaidFramesTemplate { 
         base: BaseTemplate::new(true),
-        players
+        players: players(&members),
+        classes: PlayerClass::ALL,
+        regions: Region::ALL,
     };
     let rendered = template.render().unwrap();
-    axum::response::Html(rendered)
+    axum::response::Html(rendered).into_response()
+}
+
+// +-----------------+
+// | raider.io Proxy |
+// +-----------------+
+// /api/keys/{region}/{realm}/{name} -> raider.io character profile, trimmed to what the
+// /keys popup renders. Responses are cached for KEYS_TTL, and an expired copy is served
+// (with an Age header) when raider.io is down.
+
+/// wrangler var, overrides the raider.io api (ex: a local stand-in for `wrangler dev`).
+pub const RAIDERIO_API_URL: &str = "RAIDERIO_API_URL";
+const RAIDERIO_API: &str = "https://raider.io/api/v1";
+/// seconds a profile is served from the cache before asking raider.io again.
+const KEYS_TTL: i64 = 60 * 10;
+const PROFILE_FIELDS: &str = "mythic_plus_scores_by_season:current,mythic_plus_recent_runs,mythic_plus_best_runs,mythic_plus_alternate_runs";
+
+#[derive(Debug)]
+pub enum KeysError {
+    UnknownRegion(String),
+    NotFound { realm: String, name: String },
+    Upstream(String),
+}
+
+impl KeysError {
+    fn status(&self) -> StatusCode {
+        match self {
+            KeysError::UnknownRegion(_) => StatusCode::BAD_REQUEST,
+            KeysError::NotFound { .. } => StatusCode::NOT_FOUND,
+            KeysError::Upstream(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            KeysError::UnknownRegion(_) => "unknown_region",
+            KeysError::NotFound { .. } => "character_not_found",
+            KeysError::Upstream(_) => "upstream_unavailable",
+        }
+    }
+}
+
+impl std::fmt::Display for KeysError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeysError::UnknownRegion(region) => write!(f, "unknown region '{}' (expected us or eu)", region),
+            KeysError::NotFound { realm, name } => write!(f, "{}-{} was not found on raider.io", name, realm),
+            KeysError::Upstream(e) => write!(f, "raider.io is unavailable: {}", e),
+        }
+    }
+}
+
+impl IntoResponse for KeysError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.code(), "message": self.to_string() });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// A raider.io profile, only the fields /keys and the leaderboard show.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CharacterKeys {
+    pub name: String,
+    pub realm: String,
+    pub race: String,
+    pub class: String,
+    pub active_spec_name: Option<String>,
+    pub active_spec_role: Option<String>,
+    pub achievement_points: u32,
+    pub thumbnail_url: String,
+    pub profile_url: String,
+    /// current season M+ score.
+    #[serde(default)]
+    pub score: f64,
+    #[serde(default)]
+    pub recent_runs: Vec<RecentRun>,
+    /// best run per dungeon this season, timed or not.
+    #[serde(default)]
+    pub best_runs: Vec<BestRun>,
+}
+
+impl CharacterKeys {
+    /// highest key timed in a dungeon this season.
+    pub fn best_timed(&self, dungeon: u32) -> Option<u32> {
+        self.best_runs
+            .iter()
+            .filter(|run| run.dungeon_id == dungeon && run.num_keystone_upgrades > 0)
+            .map(|run| run.mythic_level)
+            .max()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecentRun {
+    pub dungeon: String,
+    pub mythic_level: u32,
+    pub completed_at: String,
+    pub num_keystone_upgrades: u32,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BestRun {
+    /// blizzard's keystone dungeon id, the same ids as seasons.json.
+    #[serde(alias = "map_challenge_mode_id")]
+    pub dungeon_id: u32,
+    pub mythic_level: u32,
+    pub num_keystone_upgrades: u32,
+}
+
+// raider.io's response, before it is trimmed into CharacterKeys.
+#[derive(Deserialize)]
+struct RaiderIoProfile {
+    name: String,
+    realm: String,
+    race: String,
+    class: String,
+    active_spec_name: Option<String>,
+    active_spec_role: Option<String>,
+    achievement_points: u32,
+    thumbnail_url: String,
+    profile_url: String,
+    #[serde(default)]
+    mythic_plus_scores_by_season: Vec<RaiderIoSeasonScore>,
+    #[serde(default)]
+    mythic_plus_recent_runs: Vec<RecentRun>,
+    #[serde(default)]
+    mythic_plus_best_runs: Vec<BestRun>,
+    /// the other-affix best runs, a higher timed key can be in here.
+    #[serde(default)]
+    mythic_plus_alternate_runs: Vec<BestRun>,
+}
+
+#[derive(Deserialize)]
+struct RaiderIoSeasonScore {
+    scores: RaiderIoScores,
+}
+
+#[derive(Deserialize)]
+struct RaiderIoScores {
+    all: f64,
+}
+
+impl From<RaiderIoProfile> for CharacterKeys {
+    fn from(profile: RaiderIoProfile) -> Self {
+        let mut best_runs = profile.mythic_plus_best_runs;
+        best_runs.extend(profile.mythic_plus_alternate_runs);
+        CharacterKeys {
+            name: profile.name,
+            realm: profile.realm,
+            race: profile.race,
+            class: profile.class,
+            active_spec_name: profile.active_spec_name,
+            active_spec_role: profile.active_spec_role,
+            achievement_points: profile.achievement_points,
+            thumbnail_url: profile.thumbnail_url,
+            profile_url: profile.profile_url,
+            score: profile
+                .mythic_plus_scores_by_season
+                .first()
+                .map(|season| season.scores.all)
+                .unwrap_or_default(),
+            recent_runs: profile.mythic_plus_recent_runs,
+            best_runs,
+        }
+    }
+}
+
+pub struct KeysResponse {
+    pub keys: CharacterKeys,
+    /// set when the profile came from the cache.
+    pub age: Option<i64>,
+}
+
+/// Look a character up on raider.io, through the cache.
+pub async fn character_keys<H: HttpClient, C: LastGoodCache>(
+    client: &H,
+    cache: &C,
+    api: &str,
+    region: Region,
+    realm: &str,
+    name: &str,
+) -> Result<KeysResponse, KeysError> {
+    let realm = realm_slug(realm);
+    let name = name.trim().to_lowercase();
+    let cache_name = format!("raiderio/{}/{}/{}", region, realm, encode(&name));
+
+    let now = Utc::now();
+    let cached = match cache.get(&cache_name).await {
+        Ok(cached) => cached.and_then(|copy| {
+            let age = copy.age_seconds(now);
+            serde_json::from_str::<CharacterKeys>(&copy.body).ok().map(|keys| KeysResponse { keys, age: Some(age) })
+        }),
+        Err(e) => {
+            console_log!("{}", e);
+            None
+        }
+    };
+    if let Some(cached) = cached.as_ref().filter(|cached| cached.age.unwrap_or_default() < KEYS_TTL) {
+        return Ok(KeysResponse { keys: cached.keys.clone(), age: cached.age });
+    }
+
+    match (fetch_profile(client, api, region, &realm, &name).await, cached) {
+        (Ok(keys), _) => {
+            if let Ok(body) = serde_json::to_string(&keys) {
+                if let Err(e) = cache.put(&cache_name, &body, now).await {
+                    console_log!("{}", e);
+                }
+            }
+            Ok(KeysResponse { keys, age: None })
+        }
+        (Err(KeysError::Upstream(e)), Some(cached)) => {
+            console_log!("Serving cached {}: raider.io is unavailable: {}", cache_name, e);
+            Ok(cached)
+        }
+        (Err(e), _) => Err(e),
+    }
+}
+
+/// One uncached raider.io lookup, realm and name already slugged.
+pub(crate) async fn fetch_profile<H: HttpClient>(
+    client: &H,
+    api: &str,
+    region: Region,
+    realm: &str,
+    name: &str,
+) -> Result<CharacterKeys, KeysError> {
+    let url = format!(
+        "{}/characters/profile?region={}&realm={}&name={}&fields={}",
+        api.trim_end_matches('/'),
+        region,
+        encode(realm),
+        encode(name),
+        PROFILE_FIELDS
+    );
+    let response = client
+        .get(&url, &[("accept", "application/json")])
+        .await
+        .map_err(KeysError::Upstream)?;
+    match response.status {
+        // raider.io answers 400 "Could not find requested character" for unknown characters.
+        400 | 404 => Err(KeysError::NotFound {
+            realm: realm.to_string(),
+            name: name.to_string(),
+        }),
+        _ if response.is_success() => serde_json::from_str::<RaiderIoProfile>(&response.body)
+            .map(CharacterKeys::from)
+            .map_err(|e| KeysError::Upstream(format!("unexpected response: {}", e))),
+        status => Err(KeysError::Upstream(format!("status {}", status))),
+    }
+}
+
+/// RAIDERIO_API_URL when it is set, otherwise raider.io.
+pub(crate) fn raiderio_api(env: &Env) -> String {
+    env.var(RAIDERIO_API_URL)
+        .map(|api| api.to_string())
+        .unwrap_or_else(|_| RAIDERIO_API.to_string())
+}
+
+// percent encode a path or query value, names can be "Stormßreeð".
+pub(crate) fn encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+/// GET /api/keys/{region}/{realm}/{name}
+#[worker::send]
+pub async fn keys_api_handler(
+    State(env): State<Env>,
+    Path((region, realm, name)): Path<(String, String, String)>,
+) -> Response {
+    let api = raiderio_api(&env);
+    let keys = match region.parse::<Region>() {
+        Ok(region) => character_keys(&FetchClient, &WorkerCache::default(), &api, region, &realm, &name).await,
+        Err(_) => Err(KeysError::UnknownRegion(region.clone())),
+    };
+    match keys {
+        Ok(response) => {
+            let age = response.age;
+            let mut response = Json(response.keys).into_response();
+            let headers = response.headers_mut();
+            if let Ok(cache) = HeaderValue::from_str(&format!("public, max-age={}", KEYS_TTL)) {
+                headers.insert(header::CACHE_CONTROL, cache);
+            }
+            if let Some(age) = age {
+                headers.insert(header::AGE, HeaderValue::from(age));
+            }
+            response
+        }
+        Err(e) => {
+            console_log!("/api/keys/{}/{}/{}: {}", region, realm, name, e);
+            e.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{block_on, MemoryCache, StubClient};
+    use chrono::Duration;
+
+    const API: &str = "http://localhost:8788/api/v1/";
+    const CACHE_NAME: &str = "raiderio/us/area-52/aphitari";
+
+    fn profile(score: f64) -> String {
+        serde_json::json!({
+            "name": "Aphitari",
+            "realm": "Area 52",
+            "race": "Dracthyr",
+            "class": "Evoker",
+            "active_spec_name": "Devastation",
+            "active_spec_role": "DPS",
+            "achievement_points": 21000,
+            "thumbnail_url": "https://render.worldofwarcraft.com/aphitari.jpg",
+            "profile_url": "https://raider.io/characters/us/area-52/Aphitari",
+            "mythic_plus_scores_by_season": [{ "scores": { "all": score } }],
+            "mythic_plus_best_runs": [{ "map_challenge_mode_id": 500, "mythic_level": 12, "num_keystone_upgrades": 1 }],
+            "mythic_plus_alternate_runs": [{ "map_challenge_mode_id": 500, "mythic_level": 14, "num_keystone_upgrades": 1 }]
+        })
+        .to_string()
+    }
+
+    /// a copy of a 2000 score profile stored `minutes` ago.
+    fn cached(minutes: i64) -> MemoryCache {
+        let cache = MemoryCache::default();
+        let keys: CharacterKeys = serde_json::from_str::<RaiderIoProfile>(&profile(2000.0)).unwrap().into();
+        let body = serde_json::to_string(&keys).unwrap();
+        block_on(cache.put(CACHE_NAME, &body, Utc::now() - Duration::minutes(minutes))).unwrap();
+        cache
+    }
+
+    fn lookup(client: &StubClient, cache: &MemoryCache) -> Result<KeysResponse, KeysError> {
+        block_on(character_keys(client, cache, API, Region::Us, "Area 52", "Aphitari"))
+    }
+
+    #[test]
+    fn fetches_and_caches_a_profile() {
+        let client = StubClient::new(&[(200, &profile(2850.5))]);
+        let cache = MemoryCache::default();
+        let response = lookup(&client, &cache).unwrap();
+
+        assert_eq!(response.age, None);
+        assert_eq!(response.keys.score, 2850.5);
+        // alternate runs count towards the best timed key.
+        assert_eq!(response.keys.best_timed(500), Some(14));
+        assert!(block_on(cache.get(CACHE_NAME)).unwrap().is_some());
+    }
+
+    #[test]
+    fn fresh_copy_skips_raiderio() {
+        let client = StubClient::default();
+        let response = lookup(&client, &cached(5)).unwrap();
+
+        assert_eq!(client.request_count(), 0);
+        assert_eq!(response.keys.score, 2000.0);
+        assert!(response.age.is_some_and(|age| age >= 5 * 60));
+    }
+
+    #[test]
+    fn expired_copy_is_refreshed() {
+        let client = StubClient::new(&[(200, &profile(2100.0))]);
+        let response = lookup(&client, &cached(60)).unwrap();
+
+        assert_eq!(response.age, None);
+        assert_eq!(response.keys.score, 2100.0);
+    }
+
+    #[test]
+    fn expired_copy_is_served_when_raiderio_is_down() {
+        let client = StubClient::new(&[(503, "Service Unavailable")]);
+        let response = lookup(&client, &cached(60)).unwrap();
+
+        assert_eq!(response.keys.score, 2000.0);
+        assert!(response.age.is_some_and(|age| age >= 60 * 60));
+
+        let client = StubClient::default();
+        client.fail("connection reset");
+        let response = lookup(&client, &cached(60)).unwrap();
+        assert!(response.age.is_some_and(|age| age >= 60 * 60));
+    }
+
+    #[test]
+    fn unknown_character_is_not_served_from_the_cache() {
+        for status in [400, 404] {
+            let client = StubClient::new(&[(status, r#"{"error":"Could not find requested character"}"#)]);
+
+            match lookup(&client, &cached(60)) {
+                Err(KeysError::NotFound { realm, name }) => {
+                    assert_eq!(realm, "area-52");
+                    assert_eq!(name, "aphitari");
+                }
+                result => panic!("expected NotFound for {}, got {:?}", status, result.map(|response| response.age)),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_profile_is_an_upstream_error() {
+        let client = StubClient::new(&[(200, r#"{"name":"Aphitari"}"#)]);
+
+        let error = lookup(&client, &MemoryCache::default()).map(|response| response.age).unwrap_err();
+        assert!(matches!(error, KeysError::Upstream(ref e) if e.starts_with("unexpected response")), "{:?}", error);
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[test]
+    fn server_error_without_a_copy_is_an_upstream_error() {
+        let client = StubClient::new(&[(500, "oops")]);
+
+        let error = lookup(&client, &MemoryCache::default()).map(|response| response.age).unwrap_err();
+        assert!(matches!(error, KeysError::Upstream(ref e) if e == "status 500"), "{:?}", error);
+    }
+
+    #[test]
+    fn realm_and_name_are_slugged_and_encoded() {
+        let client = StubClient::new(&[(200, &profile(1000.0))]);
+        block_on(character_keys(&client, &MemoryCache::default(), API, Region::Eu, "Area 52", " Stormßreeð ")).unwrap();
+
+        assert_eq!(
+            client.requests.borrow()[0],
+            format!(
+                "http://localhost:8788/api/v1/characters/profile?region=eu&realm=area-52&name=storm%C3%9Free%C3%B0&fields={}",
+                PROFILE_FIELDS
+            )
+        );
+    }
 }
@@ -0,0 +1,365 @@
+use askama_axum::Template;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use chrono::DateTime;
+use worker::Env;
+
+use crate::cache::WorkerCache;
+use crate::dps_sims::{member_sim, Player as SimPlayer};
+use crate::http::FetchClient;
+use crate::mythic_plus::{character_keys, encode, raiderio_api, CharacterKeys, RecentRun};
+use crate::player_metadata::{build_raid, load_members, load_rosters, Member, MemberDocument, Player, RaidMetaData, RosterDocument};
+use crate::realm::{armory_url, raiderio_url, warcraftlogs_url};
+use crate::season::{load_seasons, SeasonConfig};
+use crate::storage::Store;
+use crate::time::Region;
+use crate::wow::PlayerClass;
+use crate::{error_page, BaseTemplate};
+
+// +----------------+
+// | Player Profile |
+// +----------------+
+// /player/{region}/{realm}/{name} collects a character's guild history: the season rosters and
+// kills they (or any character of their member) were part of, their sim and their raider.io
+// profile through the /api/keys cache. The raid-box popups on the home page link here.
+
+/// recent runs shown under the M+ score.
+const RECENT_RUNS: usize = 5;
+
+/// The character a profile is about, spelled the way members.json or rosters.json spell it.
+struct Character<'a> {
+    name: String,
+    realm: String,
+    region: Region,
+    class: PlayerClass,
+    member: Option<&'a Member>,
+    /// listed on /keys, so it has a score history.
+    keys: bool,
+}
+
+// members.json first, then any roster that lists the character.
+fn find_character<'a>(
+    members: &'a MemberDocument,
+    rosters: &RosterDocument,
+    region: Region,
+    realm: &str,
+    name: &str,
+) -> Option<Character<'a>> {
+    if let Some(member) = members.find_character(name, realm, region) {
+        let character = member.characters().find(|character| character.is(name, realm, region))?;
+        return Some(Character {
+            name: character.name.clone(),
+            realm: character.realm.clone(),
+            region: character.region,
+            class: character.class,
+            member: Some(member),
+            keys: character.keys,
+        });
+    }
+    let player = rosters
+        .rosters
+        .values()
+        .flatten()
+        .find(|player| player.is(name, realm, region))?;
+    Some(Character {
+        name: player.name.clone(),
+        realm: player.realm.clone(),
+        region: player.region,
+        class: player.class,
+        member: members.for_player(player),
+        keys: false,
+    })
+}
+
+/// A season roster the character, or another character of their member, was on.
+struct RosterSpot {
+    /// roster id in rosters.json (the fight_key of its kill, ex: "Gallywix").
+    roster: String,
+    /// "Season 3 of The War Within", None for a roster no kill points at.
+    season: Option<String>,
+    player: Player,
+}
+
+/// A kill the character, or another character of their member, was present for.
+struct KillSpot {
+    kill: RaidMetaData,
+    season: String,
+    player: Player,
+}
+
+/// The rosters and kills of a character, or of every character of their member.
+/// Rosters are ordered like `raids` (newest kill first), rosters without a kill last.
+fn history(
+    members: &MemberDocument,
+    rosters: &RosterDocument,
+    raids: &[RaidMetaData],
+    seasons: &SeasonConfig,
+    character: &Character,
+) -> (Vec<RosterSpot>, Vec<KillSpot>) {
+    let is_theirs = |player: &Player| match character.member {
+        Some(member) => members.for_player(player).is_some_and(|owner| owner.id == member.id),
+        None => player.is(&character.name, &character.realm, character.region),
+    };
+    let mut spots: Vec<RosterSpot> = rosters
+        .rosters
+        .iter()
+        .filter_map(|(roster, players)| {
+            let player = players.iter().find(|player| is_theirs(player))?;
+            Some(RosterSpot {
+                roster: roster.clone(),
+                season: raids
+                    .iter()
+                    .find(|kill| &kill.fight_key == roster)
+                    .map(|kill| seasons.season_label(&kill.season)),
+                player: player.clone(),
+            })
+        })
+        .collect();
+    spots.sort_by_key(|spot| raids.iter().position(|kill| kill.fight_key == spot.roster).unwrap_or(usize::MAX));
+    let kills = raids
+        .iter()
+        .filter_map(|kill| {
+            let spot = spots.iter().find(|spot| spot.roster == kill.fight_key)?;
+            Some(KillSpot {
+                kill: kill.clone(),
+                season: seasons.season_label(&kill.season),
+                player: spot.player.clone(),
+            })
+        })
+        .collect();
+    (spots, kills)
+}
+
+#[derive(Template)]
+#[template(path = "player-profile.html")]
+struct ProfileTemplate<'a> {
+    base: BaseTemplate,
+    /// the member's display name, or the character's name for characters no member lists.
+    title: String,
+    name: String,
+    /// see Region::realm_label.
+    realm_label: String,
+    class: PlayerClass,
+    member: Option<&'a Member>,
+    armory_url: String,
+    raiderio_url: String,
+    warcraftlogs_url: String,
+    /// /keys/history/{name}, for characters on /keys.
+    history_link: Option<String>,
+    rosters: Vec<RosterSpot>,
+    kills: Vec<KillSpot>,
+    sim: Option<SimPlayer>,
+    keys: Option<CharacterKeys>,
+    keys_error: Option<String>,
+}
+
+impl ProfileTemplate<'_> {
+    /// the character this page is for, among their member's characters.
+    fn is_current(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+
+    fn recent_runs(&self) -> &[RecentRun] {
+        match &self.keys {
+            Some(keys) => &keys.recent_runs[..keys.recent_runs.len().min(RECENT_RUNS)],
+            None => &[],
+        }
+    }
+
+    /// "Oct 14", or raider.io's timestamp if it doesn't parse.
+    fn run_date(&self, run: &RecentRun) -> String {
+        DateTime::parse_from_rfc3339(&run.completed_at)
+            .map(|date| date.format("%b %-d").to_string())
+            .unwrap_or_else(|_| run.completed_at.clone())
+    }
+}
+
+/// GET /player/{region}/{realm}/{name}
+#[worker::send]
+pub async fn profile_page(
+    State(env): State<Env>,
+    Path((region, realm, name)): Path<(String, String, String)>,
+) -> Response {
+    let Ok(region) = region.parse::<Region>() else {
+        return error_page(
+            StatusCode::NOT_FOUND,
+            "Character not found",
+            &format!("'{}' is not a region (expected us or eu).", region),
+        );
+    };
+    let store = Store::from_env(&env);
+    let seasons = match load_seasons(&store).await {
+        Ok(seasons) => seasons,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Profile unavailable", &e.to_string()),
+    };
+    let rosters = match load_rosters(&store).await {
+        Ok(rosters) => rosters,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Profile unavailable", &e.to_string()),
+    };
+    let members = match load_members(&store).await {
+        Ok(members) => members,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Profile unavailable", &e.to_string()),
+    };
+    let raids = match build_raid(&store, &seasons).await {
+        Ok(kills) => kills,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Profile unavailable", &e.to_string()),
+    };
+
+    let Some(character) = find_character(&members, &rosters, region, &realm, &name) else {
+        return error_page(
+            StatusCode::NOT_FOUND,
+            "Character not found",
+            &format!("{}-{} isn't on a roster or in the member list.", name, realm),
+        );
+    };
+
+    let (spots, kills) = history(&members, &rosters, &raids, &seasons, &character);
+
+    // the page still renders without raider.io, old characters are often gone from it.
+    let api = raiderio_api(&env);
+    let (keys, keys_error) = match character_keys(
+        &FetchClient,
+        &WorkerCache::default(),
+        &api,
+        character.region,
+        &character.realm,
+        &character.name,
+    )
+    .await
+    {
+        Ok(response) => (Some(response.keys), None),
+        Err(e) => {
+            console_log!("Profile {}-{}: {}", character.name, character.realm, e);
+            (None, Some(e.to_string()))
+        }
+    };
+
+    let template = ProfileTemplate {
+        base: BaseTemplate::new(false),
+        title: character
+            .member
+            .map(|member| member.display_name.clone())
+            .unwrap_or_else(|| character.name.clone()),
+        realm_label: character.region.realm_label(&character.realm),
+        class: character.class,
+        member: character.member,
+        armory_url: armory_url(character.region, &character.realm, &character.name),
+        raiderio_url: raiderio_url(character.region, &character.realm, &character.name),
+        warcraftlogs_url: warcraftlogs_url(character.region, &character.realm, &character.name),
+        history_link: character
+            .keys
+            .then(|| format!("/keys/history/{}", encode(&character.name.to_lowercase()))),
+        rosters: spots,
+        kills,
+        sim: character.member.and_then(|member| member_sim(&members, &member.id)),
+        keys,
+        keys_error,
+        name: character.name,
+    };
+    let mut response = Html(template.render().unwrap()).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, "public, max-age=600".parse().unwrap());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player_metadata::RaidManifest;
+
+    // the bundled copies the worker falls back to.
+    struct Documents {
+        seasons: SeasonConfig,
+        members: MemberDocument,
+        rosters: RosterDocument,
+        raids: Vec<RaidMetaData>,
+    }
+
+    fn documents() -> Documents {
+        let seasons = SeasonConfig::from_json(include_str!("../templates/assets/seasons.json")).unwrap();
+        let raids = RaidManifest::from_json(include_str!("../templates/assets/raids.json"), &seasons).unwrap();
+        Documents {
+            members: MemberDocument::from_json(include_str!("../templates/assets/members.json")).unwrap(),
+            rosters: RosterDocument::from_json(include_str!("../templates/assets/rosters.json")).unwrap(),
+            raids: raids.kills,
+            seasons,
+        }
+    }
+
+    // (roster, character) for every roster spot, and the fight_key of every kill.
+    fn history_of(docs: &Documents, realm: &str, name: &str) -> (Vec<(String, String)>, Vec<String>) {
+        let character = find_character(&docs.members, &docs.rosters, Region::Us, realm, name)
+            .unwrap_or_else(|| panic!("{}-{} not found", name, realm));
+        let (spots, kills) = history(&docs.members, &docs.rosters, &docs.raids, &docs.seasons, &character);
+        (
+            spots.into_iter().map(|spot| (spot.roster, spot.player.name)).collect(),
+            kills.into_iter().map(|spot| spot.kill.fight_key).collect(),
+        )
+    }
+
+    fn spots(spots: &[(&str, &str)]) -> Vec<(String, String)> {
+        spots.iter().map(|(roster, name)| (roster.to_string(), name.to_string())).collect()
+    }
+
+    #[test]
+    fn member_history_covers_their_alts() {
+        let docs = documents();
+        let expected = spots(&[("Dimensius", "Piptide"), ("Gallywix", "Pipmeow"), ("Kyvesa", "Piptide")]);
+
+        // the same history from the main's page and the alt's.
+        for name in ["Piptide", "Pipmeow"] {
+            let (rosters, kills) = history_of(&docs, "Tichondrius", name);
+            assert_eq!(rosters, expected, "{}", name);
+            assert_eq!(kills, ["Dimensius", "Gallywix", "Kyvesa"], "{}", name);
+        }
+    }
+
+    #[test]
+    fn realm_moves_follow_the_member_id() {
+        let docs = documents();
+        let (rosters, kills) = history_of(&docs, "Ysera", "Kolzane");
+
+        assert_eq!(rosters, spots(&[("Dimensius", "Kolzane"), ("Gallywix", "Kolzane"), ("Kyvesa", "Kolzane")]));
+        assert_eq!(kills, ["Dimensius", "Gallywix", "Kyvesa"]);
+        // the Kyvesa spot is the Stormrage character.
+        let character = find_character(&docs.members, &docs.rosters, Region::Us, "Ysera", "Kolzane").unwrap();
+        let (spots, _) = history(&docs.members, &docs.rosters, &docs.raids, &docs.seasons, &character);
+        assert_eq!(spots[2].player.realm, "Stormrage");
+    }
+
+    #[test]
+    fn characters_only_on_rosters() {
+        let docs = documents();
+        let character = find_character(&docs.members, &docs.rosters, Region::Us, "Stormrage", "Paliduh").unwrap();
+        assert!(character.member.is_none());
+
+        let (rosters, kills) = history_of(&docs, "Stormrage", "Paliduh");
+        assert_eq!(rosters, spots(&[("Kyvesa", "Paliduh"), ("Fyrakk", "Paliduh")]));
+        assert_eq!(kills, ["Kyvesa", "Fyrakk"]);
+    }
+
+    #[test]
+    fn rosters_without_a_kill_come_last() {
+        let mut docs = documents();
+        let piptide = docs.rosters.rosters["Dimensius"].iter().find(|player| player.name == "Piptide").unwrap().clone();
+        // "Aaa" sorts first in rosters.json, but has no kill to order it by.
+        docs.rosters.rosters.insert("Aaa".to_string(), vec![piptide]);
+        let character = find_character(&docs.members, &docs.rosters, Region::Us, "Tichondrius", "Piptide").unwrap();
+        let (spots, kills) = history(&docs.members, &docs.rosters, &docs.raids, &docs.seasons, &character);
+
+        let order: Vec<&str> = spots.iter().map(|spot| spot.roster.as_str()).collect();
+        assert_eq!(order, ["Dimensius", "Gallywix", "Kyvesa", "Aaa"]);
+        assert!(spots[..3].iter().all(|spot| spot.season.is_some()));
+        assert_eq!(spots[3].season, None);
+        assert_eq!(kills.len(), 3);
+    }
+
+    #[test]
+    fn unknown_characters_have_no_profile() {
+        let docs = documents();
+        assert!(find_character(&docs.members, &docs.rosters, Region::Us, "Stormrage", "Nobody").is_none());
+        assert!(find_character(&docs.members, &docs.rosters, Region::Eu, "Tichondrius", "Piptide").is_none());
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::LastGoodCache;
+use crate::content::{load_latest, lookup, Latest};
+use crate::realm::raiderio_guild_url;
+use crate::season::SeasonConfig;
+use crate::storage::ContentStore;
+use crate::time::Region;
+
+// +---------------+
+// | progress.json |
+// +---------------+
+// Current progression boss from warcraftlogs (served from R2 as /progress).
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Progress {
+    pub name: String,
+    pub icon_image_url: String,
+    pub pull_count: u32,
+    pub best_percent_for_display: DisplayValue,
+}
+
+/// warcraftlogs sends some display values as text and others as numbers.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum DisplayValue {
+    Text(String),
+    Number(f64),
+}
+
+impl std::fmt::Display for DisplayValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisplayValue::Text(text) => write!(f, "{}", text),
+            DisplayValue::Number(number) => write!(f, "{}%", number),
+        }
+    }
+}
+
+// +---------------+
+// | Progress Card |
+// +---------------+
+
+const GUILD_NAME: &str = "Seems Good";
+const GUILD_REALM: &str = "Stormrage";
+const GUILD_REGION: Region = Region::Us;
+/// progress.json uploaded longer ago than this is shown as stale.
+const STALE_AFTER_SECONDS: i64 = 24 * 60 * 60;
+
+pub struct ProgressCard {
+    /// current raid from seasons.json
+    pub raid_name: String,
+    /// "Season 3 of The War Within"
+    pub season_label: String,
+    pub wcl_progress_url: Option<String>,
+    pub raiderio_url: String,
+    /// None when progress.json is missing or could not be read.
+    pub boss: Option<Progress>,
+    /// set when the boss data is a last known good copy or an old upload, ex: "3 days ago".
+    pub stale: Option<String>,
+}
+
+/// Build the home page's progress card. A missing or broken progress.json leaves `boss` empty.
+pub async fn load_progress_card<S: ContentStore, C: LastGoodCache>(
+    store: &S,
+    last_good: &C,
+    seasons: &SeasonConfig,
+) -> ProgressCard {
+    let raid = seasons.current_raid();
+    let mut card = ProgressCard {
+        raid_name: raid.map(|raid| raid.name.clone()).unwrap_or_default(),
+        season_label: seasons.season_label(&seasons.current_season),
+        wcl_progress_url: raid.and_then(|raid| raid.wcl_progress_url()),
+        raiderio_url: raiderio_guild_url(GUILD_REGION, GUILD_REALM, GUILD_NAME),
+        boss: None,
+        stale: None,
+    };
+    let Some(entry) = lookup("progress.json") else {
+        return card;
+    };
+    let Some(latest) = load_latest(store, last_good, entry).await else {
+        return card;
+    };
+    match serde_json::from_str::<Progress>(&latest.object.body) {
+        Ok(progress) => {
+            card.boss = Some(progress);
+            card.stale = stale_age(&latest, Utc::now()).map(format_age);
+        }
+        Err(e) => console_log!("progress.json is malformed: {}", e),
+    }
+    card
+}
+
+// seconds since the data was written, when worth showing: always for a last known good copy, for
+// R2's copy once it is older than STALE_AFTER_SECONDS.
+fn stale_age(latest: &Latest, now: DateTime<Utc>) -> Option<i64> {
+    if latest.age.is_some() {
+        return latest.age;
+    }
+    let age = (now - latest.object.uploaded?).num_seconds();
+    (age >= STALE_AFTER_SECONDS).then_some(age)
+}
+
+// "45 minutes ago", "3 hours ago", "2 days ago"
+fn format_age(seconds: i64) -> String {
+    let (value, unit) = match seconds {
+        s if s < 60 * 60 => (s / 60, "minute"),
+        s if s < 60 * 60 * 24 => (s / (60 * 60), "hour"),
+        s => (s / (60 * 60 * 24), "day"),
+    };
+    let plural = if value == 1 { "" } else { "s" };
+    format!("{} {}{} ago", value, unit, plural)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StoredObject;
+    use chrono::Duration;
+
+    fn uploaded(now: DateTime<Utc>, hours_ago: i64) -> Latest {
+        let mut object = StoredObject::from_body("{}");
+        object.uploaded = Some(now - Duration::hours(hours_ago));
+        Latest { object, age: None }
+    }
+
+    #[test]
+    fn recent_uploads_are_not_stale() {
+        let now = Utc::now();
+
+        assert_eq!(stale_age(&uploaded(now, 2), now), None);
+    }
+
+    #[test]
+    fn old_uploads_are_stale() {
+        let now = Utc::now();
+        let age = stale_age(&uploaded(now, 50), now);
+
+        assert_eq!(age.map(format_age).as_deref(), Some("2 days ago"));
+    }
+
+    #[test]
+    fn last_known_good_copies_are_stale() {
+        let latest = Latest {
+            object: StoredObject::from_body("{}"),
+            age: Some(45 * 60),
+        };
+
+        assert_eq!(stale_age(&latest, Utc::now()).map(format_age).as_deref(), Some("45 minutes ago"));
+    }
+
+    #[test]
+    fn bundled_copy_has_no_age() {
+        let latest = Latest {
+            object: StoredObject::from_body("{}"),
+            age: None,
+        };
+
+        assert_eq!(stale_age(&latest, Utc::now()), None);
+    }
+}
@@ -0,0 +1,67 @@
+use axum::extract::Query;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::realm::{search_realms, Realm};
+use crate::time::Region;
+
+// +------------------+
+// | Realm Suggestion |
+// +------------------+
+// /api/realms?q=area&region=us autocompletes realm inputs (the /keys add-character form) from
+// the realm catalogue in seemsgood_domain::realm.
+
+const SUGGESTIONS: usize = 10;
+
+#[derive(Debug, Serialize)]
+pub struct RealmSuggestion {
+    pub name: &'static str,
+    pub slug: String,
+    pub region: String,
+    /// realms it is connected to.
+    pub connected: Vec<&'static str>,
+}
+
+impl From<Realm> for RealmSuggestion {
+    fn from(realm: Realm) -> Self {
+        Self {
+            name: realm.name,
+            slug: realm.slug(),
+            region: realm.region.to_string(),
+            connected: realm.connected().iter().map(|realm| realm.name).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RealmQuery {
+    q: Option<String>,
+    region: Option<String>,
+}
+
+/// GET /api/realms?q=&region=us|eu, both regions when region is left out.
+/// Russian realms aren't in the catalogue, so they never come back from here.
+pub async fn realms_api_handler(Query(query): Query<RealmQuery>) -> Response {
+    let region = match query.region.as_deref().filter(|region| !region.trim().is_empty()) {
+        Some(region) => match region.parse::<Region>() {
+            Ok(region) => Some(region),
+            Err(e) => {
+                let body = serde_json::json!({ "error": "unknown_region", "message": e });
+                return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+            }
+        },
+        None => None,
+    };
+    let suggestions: Vec<RealmSuggestion> = search_realms(region, query.q.as_deref().unwrap_or_default(), SUGGESTIONS)
+        .into_iter()
+        .map(RealmSuggestion::from)
+        .collect();
+    let mut response = Json(suggestions).into_response();
+    // the catalogue only changes on deploy.
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, "public, max-age=86400".parse().unwrap());
+    response
+}
@@ -0,0 +1,184 @@
+use crate::content::lookup;
+use crate::http::HttpClient;
+use crate::storage::ContentStore;
+use crate::wowaudit::{parse_characters, HistoricalData};
+
+// +------------------+
+// | wowaudit Refresh |
+// +------------------+
+// Run by the cron trigger in wrangler.toml, replaces `sgctl events fetch` + a manual upload.
+// Each document is only written when the new response parses, otherwise R2 keeps the previous one.
+
+/// wrangler secret, wowaudit's api key for the guild.
+pub const WOWAUDIT_TOKEN: &str = "WOWAUDIT_TOKEN";
+const WOWAUDIT_API: &str = "https://wowaudit.com/v1";
+
+struct AuditDocument {
+    /// CONTENT_MANIFEST name the response is stored as.
+    name: &'static str,
+    path: &'static str,
+    validate: fn(&str) -> Result<(), String>,
+}
+
+const AUDIT_DOCUMENTS: [AuditDocument; 2] = [
+    AuditDocument {
+        name: "events.json",
+        path: "/historical_data",
+        validate: validate_historical_data,
+    },
+    AuditDocument {
+        name: "characters.json",
+        path: "/characters",
+        validate: validate_characters,
+    },
+];
+
+fn validate_historical_data(json: &str) -> Result<(), String> {
+    let data = HistoricalData::from_json(json).map_err(|e| e.to_string())?;
+    if data.characters.is_empty() {
+        return Err("events.json has no characters".to_string());
+    }
+    Ok(())
+}
+
+fn validate_characters(json: &str) -> Result<(), String> {
+    parse_characters(json).map(|_| ())
+}
+
+/// what happened to one document.
+#[derive(Debug)]
+pub enum RefreshOutcome {
+    Updated { bytes: usize },
+    /// the previous copy in R2 was kept.
+    Kept { reason: String },
+}
+
+/// Fetch every wowaudit document and write the ones that validate.
+pub async fn refresh_wowaudit<S: ContentStore, H: HttpClient>(
+    store: &S,
+    client: &H,
+    token: &str,
+) -> Vec<(&'static str, RefreshOutcome)> {
+    let mut outcomes = Vec::new();
+    for document in &AUDIT_DOCUMENTS {
+        let outcome = refresh_document(store, client, token, document).await;
+        match &outcome {
+            RefreshOutcome::Updated { bytes } => console_log!("Refreshed {} ({} bytes)", document.name, bytes),
+            RefreshOutcome::Kept { reason } => console_log!("Kept previous {}: {}", document.name, reason),
+        }
+        outcomes.push((document.name, outcome));
+    }
+    outcomes
+}
+
+async fn refresh_document<S: ContentStore, H: HttpClient>(
+    store: &S,
+    client: &H,
+    token: &str,
+    document: &AuditDocument,
+) -> RefreshOutcome {
+    let kept = |reason: String| RefreshOutcome::Kept { reason };
+    let Some(entry) = lookup(document.name) else {
+        return kept(format!("{} is not in the content manifest", document.name));
+    };
+
+    let url = format!("{}{}", WOWAUDIT_API, document.path);
+    let headers = [("accept", "application/json"), ("authorization", token)];
+    let response = match client.get(&url, &headers).await {
+        Ok(response) if response.is_success() => response,
+        Ok(response) => return kept(format!("wowaudit answered {} for {}", response.status, document.path)),
+        Err(e) => return kept(format!("{} for {}", e, document.path)),
+    };
+    if let Err(e) = (document.validate)(&response.body) {
+        return kept(e);
+    }
+    match store.put(entry.key, &response.body, "application/json").await {
+        Ok(()) => RefreshOutcome::Updated {
+            bytes: response.body.len(),
+        },
+        Err(e) => kept(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStore, StoredObject};
+    use crate::testing::{block_on, StubClient};
+
+    const PREVIOUS_EVENTS: &str = r#"{"period": 1020, "characters": [{"name": "Indico", "realm": "Area 52", "data": null}]}"#;
+    const PREVIOUS_CHARACTERS: &str = "[]";
+    const CHARACTERS: &str = include_str!("../domain/fixtures/wowaudit-characters.json");
+
+    fn store() -> MemoryStore {
+        let store = MemoryStore::new();
+        store.insert(key("events.json"), StoredObject::from_body(PREVIOUS_EVENTS));
+        store.insert(key("characters.json"), StoredObject::from_body(PREVIOUS_CHARACTERS));
+        store
+    }
+
+    fn key(name: &str) -> &'static str {
+        lookup(name).unwrap().key
+    }
+
+    fn body(store: &MemoryStore, name: &str) -> String {
+        block_on(store.get(key(name))).unwrap().unwrap().body
+    }
+
+    /// refresh with this events.json response and a good characters.json.
+    fn refresh_events(client: StubClient) -> (MemoryStore, Vec<(&'static str, RefreshOutcome)>) {
+        client.respond(200, CHARACTERS);
+        let store = store();
+        let outcomes = block_on(refresh_wowaudit(&store, &client, "token"));
+        assert_eq!(client.request_count(), 2);
+        (store, outcomes)
+    }
+
+    fn kept_reason<'a>(outcomes: &'a [(&'static str, RefreshOutcome)], name: &str) -> &'a str {
+        match outcomes.iter().find(|(document, _)| *document == name) {
+            Some((_, RefreshOutcome::Kept { reason })) => reason,
+            outcome => panic!("expected {} to be kept, got {:?}", name, outcome),
+        }
+    }
+
+    #[test]
+    fn server_error_keeps_the_previous_copy() {
+        let (store, outcomes) = refresh_events(StubClient::new(&[(500, "Internal Server Error")]));
+
+        assert_eq!(kept_reason(&outcomes, "events.json"), "wowaudit answered 500 for /historical_data");
+        assert_eq!(body(&store, "events.json"), PREVIOUS_EVENTS);
+        // one failing document doesn't hold back the others.
+        assert!(matches!(outcomes[1], ("characters.json", RefreshOutcome::Updated { .. })));
+        assert_eq!(body(&store, "characters.json"), CHARACTERS);
+    }
+
+    #[test]
+    fn invalid_json_keeps_the_previous_copy() {
+        let (store, outcomes) = refresh_events(StubClient::new(&[(200, "<html>Maintenance</html>")]));
+
+        kept_reason(&outcomes, "events.json");
+        assert_eq!(body(&store, "events.json"), PREVIOUS_EVENTS);
+    }
+
+    #[test]
+    fn failed_validation_keeps_the_previous_copy() {
+        let (store, outcomes) = refresh_events(StubClient::new(&[(200, r#"{"period": 1021, "characters": []}"#)]));
+
+        assert_eq!(kept_reason(&outcomes, "events.json"), "events.json has no characters");
+        assert_eq!(body(&store, "events.json"), PREVIOUS_EVENTS);
+    }
+
+    #[test]
+    fn connection_error_keeps_the_previous_copy() {
+        let client = StubClient::default();
+        client.fail("connection reset");
+        client.respond(200, "not json");
+        let store = store();
+        let outcomes = block_on(refresh_wowaudit(&store, &client, "token"));
+
+        assert_eq!(kept_reason(&outcomes, "events.json"), "connection reset for /historical_data");
+        kept_reason(&outcomes, "characters.json");
+        assert_eq!(body(&store, "events.json"), PREVIOUS_EVENTS);
+        assert_eq!(body(&store, "characters.json"), PREVIOUS_CHARACTERS);
+    }
+}
@@ -0,0 +1,135 @@
+use askama_axum::Template;
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use worker::Env;
+
+use crate::mythic_plus::encode;
+use crate::player_metadata::{build_raid, load_members, load_rosters, MetadataError};
+use crate::roster_diff::{DiffEntry, RosterDiff, Swap};
+use crate::season::load_seasons;
+use crate::storage::Store;
+use crate::{error_page, BaseTemplate};
+
+// +-------------+
+// | Roster Diff |
+// +-------------+
+// /rosters/diff?from=Gallywix&to=Dimensius (and /api/rosters/diff for the json) compares two
+// season rosters from rosters.json, see seemsgood_domain::roster_diff. Without from/to it
+// compares the two rosters with the most recent kills.
+
+#[derive(Deserialize)]
+pub struct DiffQuery {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// A roster to pick in the from/to selects.
+struct RosterOption {
+    id: String,
+    /// "Gallywix · Season 2 of The War Within"
+    label: String,
+}
+
+// the diff plus the roster list, newest kill first (rosters without a kill last).
+async fn build_diff(env: &Env, query: DiffQuery) -> Result<(RosterDiff, Vec<RosterOption>), MetadataError> {
+    let store = Store::from_env(env);
+    let seasons = load_seasons(&store).await?;
+    let rosters = load_rosters(&store).await?;
+    let members = load_members(&store).await?;
+    let raids = build_raid(&store, &seasons).await?;
+
+    let mut options: Vec<RosterOption> = rosters
+        .rosters
+        .keys()
+        .map(|id| RosterOption {
+            id: id.clone(),
+            label: match raids.iter().find(|kill| &kill.fight_key == id) {
+                Some(kill) => format!("{} · {}", id, seasons.season_label(&kill.season)),
+                None => id.clone(),
+            },
+        })
+        .collect();
+    options.sort_by_key(|option| raids.iter().position(|kill| kill.fight_key == option.id).unwrap_or(usize::MAX));
+
+    let newest = |skip: usize| options.get(skip).or(options.first()).map(|option| option.id.clone()).unwrap_or_default();
+    let from = query.from.filter(|id| !id.trim().is_empty()).unwrap_or_else(|| newest(1));
+    let to = query.to.filter(|id| !id.trim().is_empty()).unwrap_or_else(|| newest(0));
+    let diff = RosterDiff::between(&rosters, &members, from.trim(), to.trim())?;
+    Ok((diff, options))
+}
+
+fn status(e: &MetadataError) -> StatusCode {
+    match e {
+        MetadataError::UnknownRoster(_) => StatusCode::NOT_FOUND,
+        _ => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+#[derive(Template)]
+#[template(path = "rosters-diff.html")]
+struct RosterDiffTemplate {
+    base: BaseTemplate,
+    diff: RosterDiff,
+    rosters: Vec<RosterOption>,
+}
+
+impl RosterDiffTemplate {
+    fn json_link(&self) -> String {
+        format!("/api/rosters/diff?from={}&to={}", encode(&self.diff.from), encode(&self.diff.to))
+    }
+
+    /// "Nuzzler-Stormrage", with the member's name when the guild knows them by another one.
+    fn entry_label(&self, entry: &DiffEntry) -> String {
+        match &entry.member {
+            Some(member) if !member.eq_ignore_ascii_case(&entry.player.name) => {
+                format!("{}-{} ({})", entry.player.name, entry.player.realm_label(), member)
+            }
+            _ => format!("{}-{}", entry.player.name, entry.player.realm_label()),
+        }
+    }
+
+    /// "Pip", or the character's name when members.json doesn't list them.
+    fn swap_label(&self, swap: &Swap) -> String {
+        swap.member.clone().unwrap_or_else(|| swap.after.name.clone())
+    }
+}
+
+/// GET /rosters/diff?from=&to=
+#[worker::send]
+pub async fn roster_diff_page(State(env): State<Env>, Query(query): Query<DiffQuery>) -> Response {
+    let (diff, rosters) = match build_diff(&env, query).await {
+        Ok(diff) => diff,
+        Err(e) => return error_page(status(&e), "Roster diff unavailable", &e.to_string()),
+    };
+    let template = RosterDiffTemplate {
+        base: BaseTemplate::new(false),
+        diff,
+        rosters,
+    };
+    Html(template.render().unwrap()).into_response()
+}
+
+/// GET /api/rosters/diff?from=&to=
+#[worker::send]
+pub async fn roster_diff_api_handler(State(env): State<Env>, Query(query): Query<DiffQuery>) -> Response {
+    match build_diff(&env, query).await {
+        Ok((diff, _)) => {
+            let mut response = Json(diff).into_response();
+            response
+                .headers_mut()
+                .insert(header::CACHE_CONTROL, "public, max-age=300".parse().unwrap());
+            response
+        }
+        Err(e) => {
+            let code = match e {
+                MetadataError::UnknownRoster(_) => "unknown_roster",
+                _ => "rosters_unavailable",
+            };
+            let body = serde_json::json!({ "error": code, "message": e.to_string() });
+            (status(&e), Json(body)).into_response()
+        }
+    }
+}
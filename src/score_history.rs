@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+
+use askama_axum::Template;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use worker::Env;
+
+use crate::http::HttpClient;
+use crate::mythic_plus::{fetch_profile, players, Player};
+use crate::player_metadata::{load_members, MemberDocument};
+use crate::season::load_seasons;
+use crate::storage::{ContentStore, Store};
+use crate::time::Region;
+use crate::realm::realm_slug;
+use crate::wow::PlayerClass;
+use crate::{error_page, BaseTemplate};
+
+// +------------------+
+// | M+ Score History |
+// +------------------+
+// A daily cron (SNAPSHOT_CRON in wrangler.toml) records every /keys player's raider.io score
+// into one document per season, scores/{season}.json. /keys/history/{name} charts it.
+
+/// the wrangler.toml cron that records snapshots, the other cron refreshes wowaudit.
+pub const SNAPSHOT_CRON: &str = "0 18 * * *";
+/// SNAPSHOT_CRON's hour (UTC), after the US reset (15:00) so a reset day's snapshot belongs to
+/// the week that just started.
+const SNAPSHOT_HOUR: u32 = 18;
+const SCORE_HISTORY_PREFIX: &str = "scores/";
+const SCORE_HISTORY_SCHEMA_VERSION: u32 = 1;
+/// a week with less score gained than this counts as stalled.
+const STALLED_BELOW: f64 = 1.0;
+
+const SPARKLINE_WIDTH: f64 = 320.0;
+const SPARKLINE_HEIGHT: f64 = 64.0;
+const SPARKLINE_PADDING: f64 = 4.0;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct ScoreSnapshot {
+    pub date: NaiveDate,
+    pub score: f64,
+}
+
+/// Every snapshot for one season, keyed by the player's popup id ("whare-stormrage").
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScoreHistory {
+    pub version: u32,
+    pub season: String,
+    pub characters: BTreeMap<String, Vec<ScoreSnapshot>>,
+}
+
+impl ScoreHistory {
+    fn new(season: &str) -> Self {
+        Self {
+            version: SCORE_HISTORY_SCHEMA_VERSION,
+            season: season.to_string(),
+            characters: BTreeMap::new(),
+        }
+    }
+
+    fn storage_key(season: &str) -> String {
+        format!("{}{}.json", SCORE_HISTORY_PREFIX, season)
+    }
+
+    /// add (or replace) the snapshot for `snapshot.date`, keeping them in date order.
+    pub fn record(&mut self, character: &str, snapshot: ScoreSnapshot) {
+        let snapshots = self.characters.entry(character.to_string()).or_default();
+        snapshots.retain(|existing| existing.date != snapshot.date);
+        snapshots.push(snapshot);
+        snapshots.sort_by_key(|snapshot| snapshot.date);
+    }
+
+    pub fn snapshots(&self, character: &str) -> &[ScoreSnapshot] {
+        self.characters.get(character).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// A season's score history, or an empty one when nothing has been recorded yet.
+pub async fn load_history<S: ContentStore>(store: &S, season: &str) -> Result<ScoreHistory, String> {
+    let key = ScoreHistory::storage_key(season);
+    let Some(object) = store.get(&key).await? else {
+        return Ok(ScoreHistory::new(season));
+    };
+    let history: ScoreHistory =
+        serde_json::from_str(&object.body).map_err(|e| format!("{} is not valid: {}", key, e))?;
+    if history.version != SCORE_HISTORY_SCHEMA_VERSION {
+        return Err(format!(
+            "{} version {} is not supported (expected {})",
+            key, history.version, SCORE_HISTORY_SCHEMA_VERSION
+        ));
+    }
+    Ok(history)
+}
+
+/// Record today's score for every /keys player. Characters raider.io can't return are logged
+/// and skipped, the rest are still written.
+pub async fn record_snapshots<S: ContentStore, H: HttpClient>(
+    store: &S,
+    client: &H,
+    api: &str,
+    date: NaiveDate,
+) -> Result<usize, String> {
+    let seasons = load_seasons(store).await.map_err(|e| e.to_string())?;
+    let members = load_members(store).await.map_err(|e| e.to_string())?;
+    let mut history = load_history(store, &seasons.current_season).await?;
+
+    let mut recorded = 0;
+    for player in players(&members) {
+        let name = player.name.to_lowercase();
+        match fetch_profile(client, api, player.region, &realm_slug(&player.realm), &name).await {
+            Ok(keys) => {
+                history.record(&player.popup_id(), ScoreSnapshot { date, score: keys.score });
+                recorded += 1;
+            }
+            Err(e) => console_log!("No score snapshot for {}-{}: {}", player.name, player.realm, e),
+        }
+    }
+
+    let json = serde_json::to_string(&history).map_err(|e| format!("Failed to serialize score history: {}", e))?;
+    store
+        .put(&ScoreHistory::storage_key(&history.season), &json, "application/json")
+        .await?;
+    console_log!("Recorded {} score snapshots for {}", recorded, date);
+    Ok(recorded)
+}
+
+// +-----------------+
+// | Weekly Progress |
+// +-----------------+
+
+/// the score at the end of a reset week, and what changed since the week before.
+#[derive(Debug, Clone)]
+pub struct WeeklyScore {
+    /// the Tuesday (US reset) the week starts on.
+    pub week_start: NaiveDate,
+    pub score: f64,
+    /// None for the first recorded week.
+    pub delta: Option<f64>,
+}
+
+impl WeeklyScore {
+    pub fn is_stalled(&self) -> bool {
+        self.delta.is_some_and(|delta| delta < STALLED_BELOW)
+    }
+
+    pub fn delta_label(&self) -> String {
+        match self.delta {
+            Some(delta) => format!("{:+.1}", delta),
+            None => "-".to_string(),
+        }
+    }
+}
+
+// the US reset week a snapshot taken on `date` (at SNAPSHOT_HOUR) belongs to.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    let taken_at = Utc.from_utc_datetime(&date.and_hms_opt(SNAPSHOT_HOUR, 0, 0).expect("valid hour"));
+    Region::Us.last_reset(taken_at).date_naive()
+}
+
+/// One entry per reset week that has a snapshot, oldest first. Snapshots must be in date order.
+pub fn weekly_scores(snapshots: &[ScoreSnapshot]) -> Vec<WeeklyScore> {
+    let mut weeks: Vec<WeeklyScore> = Vec::new();
+    for snapshot in snapshots {
+        let start = week_start(snapshot.date);
+        match weeks.last_mut() {
+            Some(week) if week.week_start == start => week.score = snapshot.score,
+            _ => weeks.push(WeeklyScore {
+                week_start: start,
+                score: snapshot.score,
+                delta: None,
+            }),
+        }
+    }
+    for index in 1..weeks.len() {
+        weeks[index].delta = Some(weeks[index].score - weeks[index - 1].score);
+    }
+    weeks
+}
+
+/// An inline svg line of score over time. Empty when there is nothing to draw.
+pub fn sparkline_svg(snapshots: &[ScoreSnapshot], color: &str) -> String {
+    let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) else {
+        return String::new();
+    };
+    let days = (last.date - first.date).num_days().max(1) as f64;
+    let min = snapshots.iter().map(|snapshot| snapshot.score).fold(f64::INFINITY, f64::min);
+    let max = snapshots.iter().map(|snapshot| snapshot.score).fold(f64::NEG_INFINITY, f64::max);
+    let plot_width = SPARKLINE_WIDTH - SPARKLINE_PADDING * 2.0;
+    let plot_height = SPARKLINE_HEIGHT - SPARKLINE_PADDING * 2.0;
+
+    let point = |snapshot: &ScoreSnapshot| {
+        let x = SPARKLINE_PADDING + (snapshot.date - first.date).num_days() as f64 / days * plot_width;
+        // a flat line sits in the middle.
+        let y = match max - min {
+            range if range > 0.0 => SPARKLINE_PADDING + (max - snapshot.score) / range * plot_height,
+            _ => SPARKLINE_HEIGHT / 2.0,
+        };
+        (x, y)
+    };
+    let points: Vec<String> = snapshots
+        .iter()
+        .map(|snapshot| {
+            let (x, y) = point(snapshot);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    let (last_x, last_y) = point(last);
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{w}" height="{h}" role="img" aria-label="M+ score from {from:.0} to {to:.0}"><polyline fill="none" stroke="{color}" stroke-width="2" stroke-linejoin="round" points="{points}"/><circle cx="{x:.1}" cy="{y:.1}" r="3" fill="{color}"/></svg>"#,
+        w = SPARKLINE_WIDTH,
+        h = SPARKLINE_HEIGHT,
+        from = first.score,
+        to = last.score,
+        color = color,
+        points = points.join(" "),
+        x = last_x,
+        y = last_y,
+    )
+}
+
+#[derive(Template)]
+#[template(path = "keys-history.html")]
+struct HistoryTemplate {
+    base: BaseTemplate,
+    name: String,
+    realm: String,
+    class: PlayerClass,
+    season: String,
+    /// latest recorded score.
+    score: Option<f64>,
+    sparkline: String,
+    /// newest first.
+    weeks: Vec<WeeklyScore>,
+}
+
+impl HistoryTemplate {
+    fn score_label(&self) -> String {
+        match self.score {
+            Some(score) => format!("{:.1}", score),
+            None => "-".to_string(),
+        }
+    }
+
+    fn is_stalled(&self) -> bool {
+        self.weeks.first().is_some_and(WeeklyScore::is_stalled)
+    }
+}
+
+fn find_player(members: &MemberDocument, name: &str) -> Option<Player> {
+    let name = name.trim().to_lowercase();
+    players(members).into_iter().find(|player| player.name.to_lowercase() == name)
+}
+
+/// GET /keys/history/{name}
+#[worker::send]
+pub async fn history_page(State(env): State<Env>, Path(name): Path<String>) -> Response {
+    let store = Store::from_env(&env);
+    let members = match load_members(&store).await {
+        Ok(members) => members,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Score history unavailable", &e.to_string()),
+    };
+    let Some(player) = find_player(&members, &name) else {
+        return error_page(
+            StatusCode::NOT_FOUND,
+            "Character not found",
+            &format!("{} isn't on the Mythic+ roster.", name),
+        );
+    };
+    let seasons = match load_seasons(&store).await {
+        Ok(seasons) => seasons,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Score history unavailable", &e.to_string()),
+    };
+    let history = match load_history(&store, &seasons.current_season).await {
+        Ok(history) => history,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Score history unavailable", &e),
+    };
+
+    let snapshots = history.snapshots(&player.popup_id());
+    let mut weeks = weekly_scores(snapshots);
+    weeks.reverse();
+    let template = HistoryTemplate {
+        base: BaseTemplate::new(false),
+        name: player.name.clone(),
+        realm: player.realm.clone(),
+        class: player.class,
+        season: seasons.season_label(&seasons.current_season),
+        score: snapshots.last().map(|snapshot| snapshot.score),
+        sparkline: sparkline_svg(snapshots, player.class.rgb()),
+        weeks,
+    };
+    let mut response = Html(template.render().unwrap()).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, "public, max-age=3600".parse().unwrap());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(date: &str, score: f64) -> ScoreSnapshot {
+        ScoreSnapshot {
+            date: date.parse().unwrap(),
+            score,
+        }
+    }
+
+    fn points(svg: &str) -> Vec<&str> {
+        let start = svg.find("points=\"").unwrap() + "points=\"".len();
+        let end = start + svg[start..].find('"').unwrap();
+        svg[start..end].split(' ').collect()
+    }
+
+    #[test]
+    fn snapshot_cron_runs_at_the_snapshot_hour() {
+        assert_eq!(SNAPSHOT_CRON, format!("0 {} * * *", SNAPSHOT_HOUR));
+        assert!(include_str!("../wrangler.toml").contains(&format!("\"{}\"", SNAPSHOT_CRON)));
+    }
+
+    #[test]
+    fn weeks_split_at_the_us_reset() {
+        // 2025-10-14 is a reset Tuesday.
+        let weeks = weekly_scores(&[
+            snapshot("2025-10-12", 3000.0),
+            snapshot("2025-10-13", 3010.0),
+            snapshot("2025-10-14", 3050.0),
+            snapshot("2025-10-20", 3060.0),
+            snapshot("2025-10-21", 3060.5),
+        ]);
+
+        let starts: Vec<String> = weeks.iter().map(|week| week.week_start.to_string()).collect();
+        assert_eq!(starts, ["2025-10-07", "2025-10-14", "2025-10-21"]);
+        // the week's last snapshot is its score.
+        assert_eq!(weeks[0].score, 3010.0);
+        assert_eq!(weeks[1].score, 3060.0);
+        assert_eq!(weeks[0].delta, None);
+        assert_eq!(weeks[1].delta, Some(50.0));
+        assert!(weeks[2].is_stalled());
+        assert_eq!(weeks[2].delta_label(), "+0.5");
+    }
+
+    #[test]
+    fn no_snapshots_no_weeks() {
+        assert!(weekly_scores(&[]).is_empty());
+    }
+
+    #[test]
+    fn sparkline_without_snapshots_is_empty() {
+        assert_eq!(sparkline_svg(&[], "#fff"), "");
+    }
+
+    #[test]
+    fn sparkline_with_one_snapshot_is_a_dot() {
+        let svg = sparkline_svg(&[snapshot("2025-10-14", 3000.0)], "#fff");
+
+        assert_eq!(points(&svg), ["4.0,32.0"]);
+        assert!(svg.contains(r#"<circle cx="4.0" cy="32.0""#));
+        assert!(svg.contains("M+ score from 3000 to 3000"));
+    }
+
+    #[test]
+    fn sparkline_spans_the_chart() {
+        let svg = sparkline_svg(
+            &[
+                snapshot("2025-10-14", 3000.0),
+                snapshot("2025-10-16", 2900.0),
+                snapshot("2025-10-18", 3100.0),
+            ],
+            "#C41E3A",
+        );
+
+        // highest score at the top, lowest at the bottom, days spread across the width.
+        assert_eq!(points(&svg), ["4.0,32.0", "160.0,60.0", "316.0,4.0"]);
+        assert!(svg.contains(r##"<circle cx="316.0" cy="4.0" r="3" fill="#C41E3A"/>"##));
+        assert!(svg.contains("M+ score from 3000 to 3100"));
+    }
+}
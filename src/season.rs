@@ -0,0 +1,14 @@
+use crate::player_metadata::{load_document, MetadataError};
+use crate::storage::ContentStore;
+pub use seemsgood_domain::season::{Dungeon, SeasonConfig};
+
+// +---------------+
+// | Season Config |
+// +---------------+
+// Types and validation live in seemsgood_domain::season.
+
+/// Load seasons.json from R2 (or the bundled copy).
+pub async fn load_seasons<S: ContentStore>(store: &S) -> Result<SeasonConfig, MetadataError> {
+    let json = load_document(store, "seasons.json").await?;
+    SeasonConfig::from_json(&json)
+}
@@ -0,0 +1,227 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use worker::{Bucket, Env, HttpMetadata};
+
+// +---------------+
+// | Content Store |
+// +---------------+
+// Documents are read from (and written to) the R2_CONTENT bucket binding (see wrangler.toml).
+// Handlers take any ContentStore so they can run against MemoryStore outside of Cloudflare.
+
+/// name of the R2 binding in wrangler.toml
+pub const CONTENT_BUCKET: &str = "R2_CONTENT";
+/// wrangler var, "true" lets writes land in memory when R2_CONTENT isn't bound (`wrangler dev`
+/// without the bucket). They are lost with the isolate, so production leaves it unset.
+pub const DEV_MEMORY_WRITES: &str = "DEV_MEMORY_WRITES";
+
+/// A document and the metadata R2 keeps for it.
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub body: String,
+    pub etag: Option<String>,
+    pub uploaded: Option<DateTime<Utc>>,
+}
+
+impl StoredObject {
+    /// an object with no R2 metadata (ex: a bundled fallback asset).
+    pub fn from_body(body: impl Into<String>) -> Self {
+        Self {
+            body: body.into(),
+            etag: None,
+            uploaded: None,
+        }
+    }
+}
+
+pub trait ContentStore {
+    /// Ok(None) when the key does not exist, Err when the store could not be read.
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, String>;
+    /// create or replace the object at key.
+    async fn put(&self, key: &str, body: &str, content_type: &str) -> Result<(), String>;
+    /// every key starting with prefix, sorted.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String>;
+}
+
+// R2 through the worker binding, no public https hop.
+pub struct R2Store {
+    bucket: Bucket,
+}
+
+impl R2Store {
+    pub fn new(bucket: Bucket) -> Self {
+        Self { bucket }
+    }
+}
+
+impl ContentStore for R2Store {
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, String> {
+        let object = match self
+            .bucket
+            .get(key)
+            .execute()
+            .await
+            .map_err(|e| format!("Failed to read {} from R2: {:?}", key, e))?
+        {
+            Some(object) => object,
+            None => return Ok(None),
+        };
+
+        let etag = object.http_etag();
+        let uploaded = DateTime::from_timestamp_millis(object.uploaded().as_millis() as i64);
+        let body = match object.body() {
+            Some(body) => body
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read body of {}: {:?}", key, e))?,
+            None => return Err(format!("R2 object {} has no body", key)),
+        };
+
+        Ok(Some(StoredObject {
+            body,
+            etag: Some(etag),
+            uploaded,
+        }))
+    }
+
+    async fn put(&self, key: &str, body: &str, content_type: &str) -> Result<(), String> {
+        self.bucket
+            .put(key, body.to_string())
+            .http_metadata(HttpMetadata {
+                content_type: Some(content_type.to_string()),
+                ..Default::default()
+            })
+            .execute()
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to write {} to R2: {:?}", key, e))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let mut keys = Vec::new();
+        let mut cursor = None;
+        loop {
+            let mut request = self.bucket.list().prefix(prefix);
+            if let Some(cursor) = cursor.take() {
+                request = request.cursor(cursor);
+            }
+            let page = request
+                .execute()
+                .await
+                .map_err(|e| format!("Failed to list {} in R2: {:?}", prefix, e))?;
+            keys.extend(page.objects().iter().map(|object| object.key()));
+            match page.cursor() {
+                Some(next) if page.truncated() => cursor = Some(next),
+                _ => break,
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+// Objects kept in memory, keyed like the bucket.
+#[derive(Default)]
+pub struct MemoryStore {
+    objects: RefCell<HashMap<String, StoredObject>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, key: &str, object: StoredObject) {
+        self.objects.borrow_mut().insert(key.to_string(), object);
+    }
+}
+
+impl ContentStore for MemoryStore {
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, String> {
+        Ok(self.objects.borrow().get(key).cloned())
+    }
+
+    async fn put(&self, key: &str, body: &str, _content_type: &str) -> Result<(), String> {
+        let object = StoredObject {
+            body: body.to_string(),
+            etag: None,
+            uploaded: Some(Utc::now()),
+        };
+        self.insert(key, object);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let mut keys: Vec<String> = self
+            .objects
+            .borrow()
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// The store handlers read from: R2_CONTENT when it is bound, otherwise a MemoryStore
+/// holding the bundled fallbacks so `wrangler dev` works without the bucket.
+pub enum Store {
+    R2(R2Store),
+    /// writes fail unless `writable` (DEV_MEMORY_WRITES), so nothing is silently dropped.
+    Memory { store: MemoryStore, writable: bool },
+}
+
+impl Store {
+    pub fn from_env(env: &Env) -> Self {
+        match env.bucket(CONTENT_BUCKET) {
+            Ok(bucket) => Store::R2(R2Store::new(bucket)),
+            Err(e) => {
+                console_log!("{} is not bound, using bundled content: {:?}", CONTENT_BUCKET, e);
+                let writable = env
+                    .var(DEV_MEMORY_WRITES)
+                    .is_ok_and(|flag| flag.to_string().eq_ignore_ascii_case("true"));
+                Store::bundled(writable)
+            }
+        }
+    }
+
+    /// the bundled fallbacks in memory.
+    pub fn bundled(writable: bool) -> Self {
+        let store = MemoryStore::new();
+        for entry in crate::content::CONTENT_MANIFEST {
+            if let Some(body) = entry.fallback_body() {
+                store.insert(entry.key, StoredObject::from_body(body));
+            }
+        }
+        Store::Memory { store, writable }
+    }
+}
+
+impl ContentStore for Store {
+    async fn get(&self, key: &str) -> Result<Option<StoredObject>, String> {
+        match self {
+            Store::R2(store) => store.get(key).await,
+            Store::Memory { store, .. } => store.get(key).await,
+        }
+    }
+
+    async fn put(&self, key: &str, body: &str, content_type: &str) -> Result<(), String> {
+        match self {
+            Store::R2(store) => store.put(key, body, content_type).await,
+            Store::Memory { store, writable: true } => store.put(key, body, content_type).await,
+            Store::Memory { writable: false, .. } => Err(format!(
+                "{} is not bound, {} was not written (set {}=true to keep writes in memory)",
+                CONTENT_BUCKET, key, DEV_MEMORY_WRITES
+            )),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        match self {
+            Store::R2(store) => store.list(prefix).await,
+            Store::Memory { store, .. } => store.list(prefix).await,
+        }
+    }
+}
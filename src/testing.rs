@@ -0,0 +1,100 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use chrono::{DateTime, Utc};
+
+use crate::cache::{CachedCopy, LastGoodCache};
+use crate::http::{HttpClient, HttpResponse};
+
+// +--------------+
+// | Test Helpers |
+// +--------------+
+// Stand-ins for the worker's cache and fetch() so handlers run under `cargo test`.
+
+/// Run a future to completion. MemoryStore and MemoryCache never wait on anything, so polling
+/// in a loop is enough.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// LastGoodCache kept in memory.
+#[derive(Default)]
+pub struct MemoryCache {
+    copies: RefCell<HashMap<String, CachedCopy>>,
+}
+
+impl LastGoodCache for MemoryCache {
+    async fn get(&self, name: &str) -> Result<Option<CachedCopy>, String> {
+        Ok(self.copies.borrow().get(name).cloned())
+    }
+
+    async fn put(&self, name: &str, body: &str, stored_at: DateTime<Utc>) -> Result<(), String> {
+        let copy = CachedCopy {
+            body: body.to_string(),
+            stored_at,
+        };
+        self.copies.borrow_mut().insert(name.to_string(), copy);
+        Ok(())
+    }
+}
+
+/// HttpClient answering from a script, one response per request, and recording every url.
+#[derive(Default)]
+pub struct StubClient {
+    responses: RefCell<VecDeque<Result<HttpResponse, String>>>,
+    pub requests: RefCell<Vec<String>>,
+}
+
+impl StubClient {
+    /// answer requests with these statuses and bodies, in order.
+    pub fn new(responses: &[(u16, &str)]) -> Self {
+        let client = Self::default();
+        for (status, body) in responses {
+            client.respond(*status, body);
+        }
+        client
+    }
+
+    pub fn respond(&self, status: u16, body: &str) {
+        self.responses.borrow_mut().push_back(Ok(HttpResponse {
+            status,
+            body: body.to_string(),
+        }));
+    }
+
+    /// the next request gets no response at all (dns, connection, etc.).
+    pub fn fail(&self, error: &str) {
+        self.responses.borrow_mut().push_back(Err(error.to_string()));
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.borrow().len()
+    }
+
+    fn next(&self, url: &str) -> Result<HttpResponse, String> {
+        self.requests.borrow_mut().push(url.to_string());
+        self.responses
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected request to {}", url))
+    }
+}
+
+impl HttpClient for StubClient {
+    async fn post_json(&self, url: &str, _body: &str) -> Result<HttpResponse, String> {
+        self.next(url)
+    }
+
+    async fn get(&self, url: &str, _headers: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        self.next(url)
+    }
+}
@@ -0,0 +1,100 @@
+use askama_axum::Template;
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use worker::Env;
+
+use crate::cache::WorkerCache;
+use crate::time::Region;
+use crate::storage::Store;
+use crate::wowaudit::{is_stale, load_events_updated, VaultProgress};
+use crate::{error_page, BaseTemplate};
+
+// +-------------+
+// | Great Vault |
+// +-------------+
+// /vault: every raider's vault for the week in wowaudit's historical_data (what /events serves).
+
+/// wrangler var, dungeons a raider should do each week. below it they are flagged on /vault.
+pub const VAULT_MIN_DUNGEONS: &str = "VAULT_MIN_DUNGEONS";
+const DEFAULT_MIN_DUNGEONS: usize = 4;
+
+#[derive(Template)]
+#[template(path = "vault.html")]
+struct VaultTemplate {
+    base: BaseTemplate,
+    week_start: DateTime<Utc>,
+    next_reset: DateTime<Utc>,
+    minimum: usize,
+    /// when events.json was last written, None for the bundled copy.
+    updated_at: Option<DateTime<Utc>>,
+    /// the data is from before this week's reset, flags are hidden.
+    stale: bool,
+    /// most dungeons first.
+    raiders: Vec<VaultProgress>,
+}
+
+impl VaultTemplate {
+    fn slot_label(&self, slot: &Option<u32>) -> String {
+        match slot {
+            Some(0) => "M0".to_string(),
+            Some(level) => format!("+{}", level),
+            None => "-".to_string(),
+        }
+    }
+
+    fn ilvl_label(&self, slot: &Option<u32>) -> String {
+        match slot {
+            Some(ilvl) => ilvl.to_string(),
+            None => "-".to_string(),
+        }
+    }
+
+    fn is_flagged(&self, raider: &VaultProgress) -> bool {
+        !self.stale && raider.is_below(self.minimum)
+    }
+
+    fn below_minimum(&self) -> usize {
+        self.raiders.iter().filter(|raider| self.is_flagged(raider)).count()
+    }
+}
+
+/// GET /vault
+#[worker::send]
+pub async fn vault_page(State(env): State<Env>) -> Response {
+    let minimum = env
+        .var(VAULT_MIN_DUNGEONS)
+        .ok()
+        .and_then(|minimum| minimum.to_string().parse().ok())
+        .unwrap_or(DEFAULT_MIN_DUNGEONS);
+    let store = Store::from_env(&env);
+    let (events, updated_at) = match load_events_updated(&store, &WorkerCache::default()).await {
+        Ok(loaded) => loaded,
+        Err(e) => return error_page(StatusCode::SERVICE_UNAVAILABLE, "Vault unavailable", &e.to_string()),
+    };
+
+    let mut raiders: Vec<VaultProgress> = events.characters.iter().map(VaultProgress::from_character).collect();
+    raiders.sort_by(|a, b| {
+        b.dungeons_done
+            .cmp(&a.dungeons_done)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    // wowaudit tracks the US week.
+    let now = Utc::now();
+    let week_start = Region::Us.last_reset(now);
+    let template = VaultTemplate {
+        base: BaseTemplate::new(false),
+        week_start,
+        next_reset: Region::Us.next_reset(now),
+        minimum,
+        updated_at,
+        stale: is_stale(updated_at, week_start),
+        raiders,
+    };
+    let mut response = Html(template.render().unwrap()).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, "public, max-age=300".parse().unwrap());
+    response
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+
+use crate::cache::LastGoodCache;
+use crate::content::{load_latest, lookup};
+use crate::storage::ContentStore;
+pub use seemsgood_domain::wowaudit::{parse_characters, EventsError, HistoricalData, KeyTier, MarqueeLine, VaultProgress};
+
+// +--------------------------+
+// | wowaudit historical_data |
+// +--------------------------+
+// Types live in seemsgood_domain::wowaudit, this loads them the way the worker serves them.
+
+/// Load events.json the same way /events serves it (R2, then last known good).
+pub async fn load_events<S: ContentStore, C: LastGoodCache>(
+    store: &S,
+    last_good: &C,
+) -> Result<HistoricalData, EventsError> {
+    load_events_updated(store, last_good).await.map(|(events, _)| events)
+}
+
+/// load_events, and when that copy was written (see Latest::updated_at).
+pub async fn load_events_updated<S: ContentStore, C: LastGoodCache>(
+    store: &S,
+    last_good: &C,
+) -> Result<(HistoricalData, Option<DateTime<Utc>>), EventsError> {
+    let entry = lookup("events.json").ok_or(EventsError::Unavailable)?;
+    let latest = load_latest(store, last_good, entry)
+        .await
+        .ok_or(EventsError::Unavailable)?;
+    let events = HistoricalData::from_json(&latest.object.body)?;
+    Ok((events, latest.updated_at(Utc::now())))
+}
+
+/// events.json written before `week_start` (or at an unknown time) still holds last week's runs
+/// (what /vault and the leaderboard show).
+pub fn is_stale(updated_at: Option<DateTime<Utc>>, week_start: DateTime<Utc>) -> bool {
+    updated_at.is_none_or(|updated_at| updated_at < week_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::Region;
+    use chrono::TimeZone;
+
+    #[test]
+    fn data_from_before_the_reset_is_stale() {
+        // Tuesday 2025-10-14 15:00 UTC.
+        let week_start = Region::Us.last_reset(Utc.with_ymd_and_hms(2025, 10, 15, 12, 0, 0).unwrap());
+
+        assert!(is_stale(Some(Utc.with_ymd_and_hms(2025, 10, 14, 14, 30, 0).unwrap()), week_start));
+        assert!(!is_stale(Some(Utc.with_ymd_and_hms(2025, 10, 14, 15, 30, 0).unwrap()), week_start));
+        // the bundled copy.
+        assert!(is_stale(None, week_start));
+    }
+}